@@ -1,41 +1,313 @@
-use bevy::{audio::AudioSink, prelude::*, render::camera::Camera3d, transform};
+use std::f32::consts::TAU;
+
+use bevy::{input::touch::Touches, prelude::*, render::camera::Camera3d, transform};
 use bevy_prototype_debug_lines::*;
-use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::{prelude::*, rapier::prelude::InteractionGroups};
 
-use crate::camera::{self, cursor_ndc, Ray};
+use crate::bot::Bots;
+use crate::camera::{self, cursor_ndc, screen_ndc, CameraDirector, CameraMode, CameraOrbit, Ray};
+use crate::input::{Action, ActionState, Device};
+use crate::proc::{course::Tee, terrain_brush::TerrainBrush, CurrentCourse, Ground, NewHoleEvent};
+use crate::replay::{FixedPhysicsPlugin, PhysicsClock, Playback};
 
-const BALL_RADIUS: f32 = 0.035;
+pub const BALL_RADIUS: f32 = 0.035;
 const MAX_POWER: f32 = 0.25;
-const MIN_VELOCITY: f32 = 0.01;
+/// Balls moving slower than this (squared) are at rest.
+pub const MIN_VELOCITY: f32 = 0.01;
+/// The space between each player's ball on the tee.
+const TEE_SPACING: f32 = BALL_RADIUS * 4.0;
+/// Balls that fall below this height have left the course.
+pub const KILL_HEIGHT: f32 = -10.0;
+/// Turns end once every ball has been still for this many frames.
+const MAX_STILL_FRAMES: u32 = 10;
+/// How far below a ball the ground is looked for when finding the slope it's on.
+const SLOPE_PROBE: f32 = BALL_RADIUS * 4.0;
+/// How far the cursor's ray looks for the course.
+const MAX_AIM_DISTANCE: f32 = 1000.0;
+/// How fast the arrow keys turn the aim, in radians per second.
+const AIM_TURN_SPEED: f32 = 1.5;
+/// How long a held charge takes to reach full power, in seconds.
+const CHARGE_SECONDS: f32 = 1.5;
+/// How many times a second the oscillating meter goes up and back down.
+const METER_SPEED: f32 = 0.75;
+/// How long the line showing the keyboard and gamepad aim is.
+const AIM_GUIDE_LENGTH: f32 = 0.25;
+/// How close to the ball a finger has to touch down to start aiming.
+const TOUCH_GRAB_RADIUS: f32 = 0.3;
 
 pub struct BallPlugin;
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(add_ball);
+        app.init_resource::<Players>();
+        app.init_resource::<Turn>();
+        app.init_resource::<AimMode>();
+        app.init_resource::<PadAim>();
+        app.init_resource::<TouchAim>();
+        app.add_plugin(ShotPlugin);
         app.add_startup_system(ball_sounds);
-        app.add_system(charge_ball);
-        app.add_system(fire_ball);
+        app.add_system(spawn_balls);
+        app.add_system(reset_balls);
+        app.add_system(switch_aim_mode);
+        app.add_system(
+            charge_ball
+                .after(switch_aim_mode)
+                .after(pad_aim)
+                .after(touch_aim),
+        );
+        app.add_system(pad_aim);
+        app.add_system(touch_aim.after(pad_aim));
+        app.add_system(fire_ball.before(ApplyShots).after(charge_ball));
+        app.add_system(shot_sounds);
+        app.add_system(take_turns.after(ApplyShots));
+    }
+}
+
+/// Applies ShootEvents to the balls, without any input, sound or drawing.
+pub struct ShotPlugin;
+impl Plugin for ShotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FixedPhysicsPlugin);
+        app.add_event::<ShootEvent>();
+        app.add_system(apply_shots.label(ApplyShots));
     }
 }
 
+/// Fires a ball. Everything that takes shots sends these so they all fire the same way.
+pub struct ShootEvent {
+    pub ball: Entity,
+    /// The aim vector (Ball.0) of the shot.
+    pub aim: Vec3,
+}
+
+/// Systems that send ShootEvents run before this so shots are applied on the frame they're taken.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApplyShots;
+
 #[derive(Component)]
 pub struct Ball(Vec3);
 
+/// The player a ball belongs to.
+#[derive(Component)]
+pub struct Player(pub usize);
+
+/// How many players are playing, each one gets their own ball.
+/// There's always at least one, replays without any are rejected when they're loaded.
+pub struct Players(pub usize);
+
+impl Default for Players {
+    fn default() -> Self {
+        Players(1)
+    }
+}
+
+/// The components a ball needs to roll around and be shot.
+#[derive(Bundle)]
+pub struct BallBundle {
+    pub rigid_body: RigidBody,
+    pub velocity: Velocity,
+    pub damping: Damping,
+    pub external_force: ExternalForce,
+    pub ccd: Ccd,
+    pub collider: Collider,
+    pub active_events: ActiveEvents,
+    pub ball: Ball,
+    pub player: Player,
+}
+
+impl BallBundle {
+    pub fn new(player: usize) -> Self {
+        BallBundle {
+            rigid_body: RigidBody::Dynamic,
+            velocity: Velocity::default(),
+            damping: Damping {
+                linear_damping: 0.03,
+                angular_damping: 8.0,
+            },
+            external_force: ExternalForce {
+                force: Vec3::ZERO,
+                torque: Vec3::ZERO,
+            },
+            ccd: Ccd::enabled(),
+            collider: Collider::ball(BALL_RADIUS),
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            ball: Ball(Vec3::ZERO),
+            player: Player(player),
+        }
+    }
+}
+
+/// Whose turn it is. Players take turns shooting and every ball stops before the next turn.
+#[derive(Default)]
+pub struct Turn {
+    pub player: usize,
+    /// Whether the player has taken their shot.
+    shot: bool,
+    /// How many frames the balls have been still since the shot.
+    still_frames: u32,
+}
+
+impl Turn {
+    /// Whether the current player can take their shot.
+    pub fn ready(&self) -> bool {
+        !self.shot
+    }
+}
+
+/// How the cursor is turned into an aim, Tab switches between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AimMode {
+    /// Aims at the cursor on a flat plane at the height of the ball.
+    Plane,
+    /// Aims at the part of the course under the cursor, flattened onto the slope the ball is on.
+    Course,
+}
+
+impl Default for AimMode {
+    fn default() -> Self {
+        AimMode::Course
+    }
+}
+
+/// Aiming with the keyboard or a gamepad, instead of the mouse.
+/// AimLeft, AimRight and the Aim stick turn the aim, and holding Charge on the keyboard or a gamepad charges the shot.
+#[derive(Default)]
+pub struct PadAim {
+    /// Whether the keyboard or a gamepad was used last, the mouse takes over again once it's used.
+    pub active: bool,
+    /// The direction of the shot, in radians around the Y axis from -Z.
+    pub heading: f32,
+    /// How hard the ball will be shot, from 0 to 1.
+    pub power: f32,
+    /// Whether the power swings up and down while charging instead of filling up, toggled with ToggleMeter.
+    pub oscillate: bool,
+    /// Whether the shot is being charged.
+    pub charging: bool,
+    /// Whether the charge was let go this frame, which takes the shot.
+    pub released: bool,
+    charge_seconds: f32,
+}
+
+impl PadAim {
+    fn charge(&mut self, delta_seconds: f32) {
+        self.charge_seconds += delta_seconds;
+        self.power = match self.oscillate {
+            true => (1.0 - (self.charge_seconds * METER_SPEED * TAU).cos()) * 0.5,
+            false => (self.charge_seconds / CHARGE_SECONDS).min(1.0),
+        };
+    }
+}
+
+/// Aiming by touching down near the ball and dragging back from it, letting go takes the shot.
+#[derive(Default)]
+pub struct TouchAim {
+    /// The finger that's aiming.
+    pub finger: Option<u64>,
+    /// Where the finger is, or where it let go, on the window.
+    pub position: Vec2,
+    /// Whether the finger let go this frame, which takes the shot.
+    pub released: bool,
+}
+
+impl TouchAim {
+    /// Whether a finger is aiming or has just let go.
+    pub fn aiming(&self) -> bool {
+        self.finger.is_some() || self.released
+    }
+}
+
+/// Gets the aim of a shot, the angle is around the Y axis from -Z.
+pub fn shot_aim(angle: f32, power: f32) -> Vec3 {
+    Quat::from_rotation_y(angle) * -Vec3::Z * power
+}
+
+/// Gets the angle of a direction around the Y axis from -Z, the opposite of shot_aim.
+fn heading(direction: Vec3) -> f32 {
+    f32::atan2(-direction.x, -direction.z)
+}
+
 #[derive(Component)]
 pub struct ChargeAudio {
     sound: Handle<AudioSource>,
     last_charge: f32,
 }
 
-fn add_ball(
+/// Gets where a player's ball goes on the tee.
+/// The balls are lined up across the direction the tee faces so they don't overlap.
+pub fn tee_position(tee: &Tee, player: usize, players: usize) -> Vec3 {
+    let across = tee.forward().cross(Vec3::Y);
+    let offset = (player as f32 - players.saturating_sub(1) as f32 * 0.5) * TEE_SPACING;
+    tee.world_position() + across * offset + Vec3::Y * BALL_RADIUS * 2.0
+}
+
+/// Places every player's ball on the tee at the start of a hole, adding or removing balls.
+fn spawn_balls(
     mut commands: Commands,
+    mut ev_new_hole: EventReader<NewHoleEvent>,
+    mut balls: Query<(Entity, &Player, &mut Transform, &mut Velocity)>,
+    players: Res<Players>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     asset_server: Res<AssetServer>,
+) {
+    for NewHoleEvent(tee) in ev_new_hole.iter() {
+        let mut placed = vec![false; players.0];
+
+        // Move the balls we already have back to the tee.
+        for (ent, player, mut transform, mut velocity) in balls.iter_mut() {
+            if player.0 >= players.0 {
+                commands.entity(ent).despawn();
+                continue;
+            }
+            *transform = Transform::from_translation(tee_position(tee, player.0, players.0));
+            *velocity = Velocity::default();
+            placed[player.0] = true;
+        }
+
+        // Then add balls for the players without one.
+        for player in (0..players.0).filter(|player| !placed[*player]) {
+            add_ball(
+                &mut commands,
+                &mut materials,
+                &mut meshes,
+                &asset_server,
+                tee_position(tee, player, players.0),
+                player,
+            );
+        }
+    }
+}
+
+/// Puts balls back on the tee when Reset is pressed or when they fall off the course.
+fn reset_balls(
+    mut balls: Query<(&Player, &mut Transform, &mut Velocity)>,
+    actions: Res<ActionState>,
+    players: Res<Players>,
+    course: Res<CurrentCourse>,
+) {
+    let tee = match course.tee {
+        Some(tee) => tee,
+        None => return,
+    };
+
+    for (player, mut transform, mut velocity) in balls.iter_mut() {
+        if actions.just_pressed(Action::Reset) || transform.translation.y < KILL_HEIGHT {
+            *transform = Transform::from_translation(tee_position(&tee, player.0, players.0));
+            *velocity = Velocity::default();
+        }
+    }
+}
+
+fn add_ball(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    meshes: &mut Assets<Mesh>,
+    asset_server: &AssetServer,
+    position: Vec3,
+    player: usize,
 ) {
     commands
         .spawn_bundle(PbrBundle {
-            transform: Transform::from_xyz(0.0, 1.0, 0.0),
+            transform: Transform::from_translation(position),
             mesh: meshes.add(Mesh::from(shape::Icosphere {
                 radius: BALL_RADIUS,
                 subdivisions: 2,
@@ -46,19 +318,7 @@ fn add_ball(
             }),
             ..default()
         })
-        .insert(RigidBody::Dynamic)
-        .insert(Velocity::default())
-        .insert(Damping {
-            linear_damping: 0.03,
-            angular_damping: 8.0,
-        })
-        .insert(ExternalForce {
-            force: Vec3::ZERO,
-            torque: Vec3::ZERO,
-        })
-        .insert(Ccd::enabled())
-        .insert(Collider::ball(BALL_RADIUS))
-        .insert(Ball(Vec3::ZERO))
+        .insert_bundle(BallBundle::new(player))
         .insert(camera::CameraTarget)
         .insert(ChargeAudio {
             sound: asset_server.load("sounds/pluck.ogg"),
@@ -79,36 +339,80 @@ fn ball_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 fn fire_ball(
     mut balls: Query<(
-        &mut ExternalForce,
+        Entity,
+        &Player,
         &Velocity,
         &Ball,
         &mut ChargeAudio,
         &Transform,
     )>,
-    buttons: Res<Input<MouseButton>>,
-    mut sounds: ResMut<BallSounds>,
-    audio_sinks: Res<Assets<AudioSink>>,
+    actions: Res<ActionState>,
+    mut cancelled: Local<bool>,
+    director: Res<CameraDirector>,
     audio: Res<Audio>,
     mut lines: ResMut<DebugLines>,
+    mut ev_shoot: EventWriter<ShootEvent>,
+    playback: Option<Res<Playback>>,
+    turn: Res<Turn>,
+    bots: Res<Bots>,
+    brush: Option<Res<TerrainBrush>>,
+    pad: Res<PadAim>,
+    touch: Res<TouchAim>,
 ) {
-    for (mut force, velocity, ball, mut charge_audio, transform) in balls.iter_mut() {
+    // The replay takes the shots while it's playing, and bots take their own.
+    if playback.is_some() || !turn.ready() || bots.0.contains_key(&turn.player) {
+        return;
+    }
+
+    // Fingers charge while they drag and the keyboard and gamepads charge with their own binding,
+    // otherwise the mouse charges with Charge.
+    let (charging, released) = if touch.aiming() {
+        (touch.finger.is_some(), touch.released)
+    } else if pad.active {
+        (pad.charging, pad.released)
+    } else if brush.map_or(false, |brush| brush.enabled) {
+        // The mouse is painting the ground instead.
+        return;
+    } else {
+        (
+            actions.pressed(Action::Charge),
+            actions.just_released(Action::Charge),
+        )
+    };
+
+    // Cancelling drops the shot, and nothing is charged again until the charge is let go.
+    if actions.just_pressed(Action::Cancel) && charging {
+        *cancelled = true;
+    }
+    // The press that skips the flyover doesn't start a shot as well.
+    if director.mode == CameraMode::Flyover || director.skipped_flyover {
+        *cancelled = true;
+    }
+    if *cancelled {
+        *cancelled = charging;
+        return;
+    }
+    // Shoot takes the shot straight away, instead of waiting for the charge to be let go.
+    let shoot = charging && actions.just_pressed(Action::Shoot);
+    if shoot {
+        *cancelled = true;
+    }
+
+    for (ent, player, velocity, ball, mut charge_audio, transform) in balls.iter_mut() {
+        if player.0 != turn.player {
+            continue;
+        }
+
         if (velocity.linvel.length_squared() < MIN_VELOCITY) {
-            if (buttons.just_released(MouseButton::Left)) {
-                force.force = ball.0 * MAX_POWER;
-                audio.play_with_settings(
-                    sounds.fire_sound.clone(),
-                    PlaybackSettings {
-                        repeat: false,
-                        volume: ball.0.length(),
-                        speed: 2.0 - ball.0.length(),
-                    },
-                );
+            if released || shoot {
+                ev_shoot.send(ShootEvent {
+                    ball: ent,
+                    aim: ball.0,
+                });
                 charge_audio.last_charge = f32::NEG_INFINITY;
-            } else {
-                force.force = Vec3::ZERO;
             }
 
-            if (buttons.pressed(MouseButton::Left)) {
+            if charging {
                 lines.line(transform.translation, transform.translation + ball.0, 0.0);
 
                 let charge = (ball.0.length() * 4.1).floor();
@@ -125,30 +429,279 @@ fn fire_ball(
                     );
                 }
             }
-        } else {
-            // Reset the force of this ball.
+        }
+    }
+}
+
+/// Pushes the balls that were shot this frame.
+fn apply_shots(
+    mut ev_shoot: EventReader<ShootEvent>,
+    mut balls: Query<&mut ExternalForce, With<Ball>>,
+    physics: Res<PhysicsClock>,
+) {
+    // Forces only last for the step they're applied on, so they're kept through frames the physics doesn't step on.
+    if physics.stepped {
+        for mut force in balls.iter_mut() {
             force.force = Vec3::ZERO;
         }
     }
+
+    for shot in ev_shoot.iter() {
+        if let Ok(mut force) = balls.get_mut(shot.ball) {
+            force.force = shot.aim * MAX_POWER;
+        }
+    }
+}
+
+/// Moves on to the next player once the balls have stopped after a shot.
+fn take_turns(
+    mut turn: ResMut<Turn>,
+    mut ev_shoot: EventReader<ShootEvent>,
+    mut ev_new_hole: EventReader<NewHoleEvent>,
+    players: Res<Players>,
+    balls: Query<&Velocity, With<Ball>>,
+) {
+    // The first player starts each hole.
+    if ev_new_hole.iter().count() > 0 {
+        *turn = Turn::default();
+        return;
+    }
+
+    if ev_shoot.iter().count() > 0 {
+        turn.shot = true;
+        turn.still_frames = 0;
+    }
+    if !turn.shot {
+        return;
+    }
+
+    let moving = balls
+        .iter()
+        .any(|velocity| velocity.linvel.length_squared() >= MIN_VELOCITY);
+    if moving {
+        turn.still_frames = 0;
+        return;
+    }
+
+    turn.still_frames += 1;
+    if turn.still_frames > MAX_STILL_FRAMES {
+        turn.shot = false;
+        turn.player = (turn.player + 1) % players.0;
+    }
+}
+
+fn shot_sounds(mut ev_shoot: EventReader<ShootEvent>, sounds: Res<BallSounds>, audio: Res<Audio>) {
+    for shot in ev_shoot.iter() {
+        audio.play_with_settings(
+            sounds.fire_sound.clone(),
+            PlaybackSettings {
+                repeat: false,
+                volume: shot.aim.length(),
+                speed: 2.0 - shot.aim.length(),
+            },
+        );
+    }
+}
+
+/// Aims the ball whose turn it is with the keyboard or a gamepad.
+/// The Aim stick points the way on the screen, and AimLeft and AimRight turn the aim.
+fn pad_aim(
+    actions: Res<ActionState>,
+    mut ev_cursor: EventReader<CursorMoved>,
+    mut ev_new_hole: EventReader<NewHoleEvent>,
+    mut aim: ResMut<PadAim>,
+    mut lines: ResMut<DebugLines>,
+    mut balls: Query<(&Player, &mut Ball, &Velocity, &Transform)>,
+    orbit: Res<CameraOrbit>,
+    turn: Res<Turn>,
+    time: Res<Time>,
+) {
+    // Start each hole aiming the way the tee faces.
+    for NewHoleEvent(tee) in ev_new_hole.iter() {
+        aim.heading = heading(tee.forward());
+    }
+
+    let stick = Some(actions.stick(Action::Aim)).filter(|stick| *stick != Vec2::ZERO);
+    let mut turning = 0.0;
+    if actions.pressed(Action::AimLeft) {
+        turning += 1.0;
+    }
+    if actions.pressed(Action::AimRight) {
+        turning -= 1.0;
+    }
+    // The mouse charges through charge_ball instead.
+    let by_mouse = actions.device(Action::Charge) == Some(Device::Mouse);
+    let charging = actions.pressed(Action::Charge) && !by_mouse;
+    let released = actions.just_released(Action::Charge) && !by_mouse;
+
+    if actions.just_pressed(Action::ToggleMeter) {
+        aim.oscillate = !aim.oscillate;
+        info!("Oscillating power meter: {}", aim.oscillate);
+    }
+
+    // Whichever was used last does the aiming.
+    if stick.is_some() || turning != 0.0 || charging || released {
+        aim.active = true;
+    }
+    if ev_cursor.iter().count() > 0 || (by_mouse && actions.just_pressed(Action::Charge)) {
+        aim.active = false;
+    }
+    aim.charging = aim.active && charging;
+    aim.released = aim.active && released;
+    if !aim.active {
+        return;
+    }
+
+    if let Some(stick) = stick {
+        aim.heading = heading(orbit.screen_direction(stick));
+    }
+    aim.heading += turning * AIM_TURN_SPEED * time.delta_seconds();
+
+    if aim.charging {
+        aim.charge(time.delta_seconds());
+    } else if !aim.released {
+        // Keep the power until the shot has been taken.
+        aim.power = 0.0;
+        aim.charge_seconds = 0.0;
+    }
+
+    for (player, mut ball, velocity, transform) in balls.iter_mut() {
+        if player.0 != turn.player || velocity.linvel.length_squared() > MIN_VELOCITY {
+            continue;
+        }
+        ball.0 = shot_aim(aim.heading, aim.power);
+
+        if !aim.charging {
+            let guide = shot_aim(aim.heading, AIM_GUIDE_LENGTH);
+            lines.line_colored(
+                transform.translation,
+                transform.translation + guide,
+                0.0,
+                Color::GRAY,
+            );
+        }
+    }
+}
+
+/// Starts aiming when a finger touches down near the ball whose turn it is, and shoots when it lets go.
+/// Touching with a second finger stops aiming, so the camera can be moved without taking a shot.
+fn touch_aim(
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    camera_query: Query<(&GlobalTransform, &Camera), With<Camera3d>>,
+    balls: Query<(&Player, &Transform), With<Ball>>,
+    turn: Res<Turn>,
+    mut aim: ResMut<TouchAim>,
+    mut pad: ResMut<PadAim>,
+) {
+    aim.released = false;
+
+    if let Some(finger) = aim.finger {
+        if touches.iter().count() > 1 || touches.just_cancelled(finger) {
+            aim.finger = None;
+        } else if let Some(touch) = touches.get_pressed(finger) {
+            aim.position = touch.position();
+        } else {
+            if let Some(touch) = touches.get_released(finger) {
+                aim.position = touch.position();
+            }
+            aim.finger = None;
+            aim.released = true;
+        }
+        return;
+    }
+
+    let touch = match touches.iter_just_pressed().next() {
+        Some(touch) if touches.iter().count() == 1 => touch,
+        _ => return,
+    };
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let (cam_transform, cam) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    let ball = match balls.iter().find(|(player, _)| player.0 == turn.player) {
+        Some((_, transform)) => transform.translation,
+        None => return,
+    };
+
+    let ndc = screen_ndc(window, touch.position());
+    let grabbed = Ray::from_screenspace(Some(ndc), cam, cam_transform)
+        .and_then(|ray| ray.intersect_plane(Vec3::Y, ball))
+        .map_or(false, |point| point.distance(ball) < TOUCH_GRAB_RADIUS);
+    if grabbed {
+        aim.finger = Some(touch.id());
+        aim.position = touch.position();
+        pad.active = false;
+    }
+}
+
+fn switch_aim_mode(actions: Res<ActionState>, mut mode: ResMut<AimMode>) {
+    if actions.just_pressed(Action::SwitchAimMode) {
+        *mode = match *mode {
+            AimMode::Plane => AimMode::Course,
+            AimMode::Course => AimMode::Plane,
+        };
+        info!("Aiming with {:?}", *mode);
+    }
 }
 
 fn charge_ball(
     mut balls_query: Query<(&mut Ball, &Velocity, &Transform)>,
     camera_query: Query<(&GlobalTransform, &Camera), With<Camera3d>>,
+    ground_query: Query<(), With<Ground>>,
     windows: Res<Windows>,
+    rapier: Res<RapierContext>,
+    mode: Res<AimMode>,
+    pad: Res<PadAim>,
+    touch: Res<TouchAim>,
 ) {
+    // The keyboard or a gamepad is aiming instead.
+    if pad.active {
+        return;
+    }
+
     // Send a ray from screen into the world.
     // Get our ray.
-    let window = windows
-        .get_primary()
-        .expect("No window to get cursor position from.");
+    // There's nothing to aim with when running without a window or camera.
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let (cam_transform, cam) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    // A finger that's aiming takes the place of the cursor.
+    let screen = match touch.aiming() {
+        true => Some(screen_ndc(window, touch.position)),
+        false => camera::cursor_ndc(window),
+    };
+    let ray = match Ray::from_screenspace(screen, cam, cam_transform) {
+        Some(ray) => ray,
+        None => return,
+    };
 
-    let (cam_transform, cam) = camera_query.single();
-    let ray = Ray::from_screenspace(camera::cursor_ndc(window), cam, cam_transform);
-    if ray.is_none() {
-        return;
-    }
-    let ray = ray.unwrap();
+    // Only the ground is aimed at, so the balls and walls don't get in the way.
+    let on_ground: &dyn Fn(Entity) -> bool = &|ent| ground_query.get(ent).is_ok();
+    let cast_ground = |origin: Vec3, direction: Vec3, max_distance: f32| {
+        rapier.cast_ray_and_get_normal(
+            origin,
+            direction,
+            max_distance,
+            true,
+            InteractionGroups::all(),
+            Some(on_ground),
+        )
+    };
+    let cursor_hit = match *mode {
+        AimMode::Course => cast_ground(ray.origin, ray.direction, MAX_AIM_DISTANCE)
+            .map(|(_, hit)| ray.point_at(hit.toi)),
+        AimMode::Plane => None,
+    };
 
     // Apply the appropriate forces to our balls based on our raycast.
     for (mut ball, velocity, trans) in balls_query.iter_mut() {
@@ -157,9 +710,25 @@ fn charge_ball(
             continue;
         }
 
-        let dir = (ray.intersect_plane(Vec3::Y, trans.translation) - trans.translation);
+        let target = match *mode {
+            AimMode::Plane => ray.intersect_plane(Vec3::Y, trans.translation),
+            AimMode::Course => {
+                // Balls in the air (or off the course) aim across a flat plane.
+                let slope = cast_ground(trans.translation, -Vec3::Y, SLOPE_PROBE)
+                    .map_or(Vec3::Y, |(_, hit)| hit.normal);
+                match cursor_hit {
+                    // Move the hit along the slope's normal until it's on the ball's slope.
+                    Some(hit) => Some(hit - slope * (hit - trans.translation).dot(slope)),
+                    None => ray.intersect_plane(slope, trans.translation),
+                }
+            }
+        };
+        let dir = match target {
+            Some(target) => target - trans.translation,
+            None => continue,
+        };
 
         let power = dir.length().powi(2).clamp(0.0, MAX_POWER) / MAX_POWER;
-        ball.0 = -dir.normalize() * power;
+        ball.0 = -dir.normalize_or_zero() * power;
     }
 }
@@ -0,0 +1,54 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use bevy_golf::{
+    headless::asset_file,
+    proc::{
+        course::Course,
+        export::{course_meshes, export_name, write_all},
+        load_tile_packs_from,
+    },
+};
+
+/// Exports a course's ground and walls to glTF and OBJ.
+/// Usage: bevy-golf-export <course> [output]
+/// The course is relative to the assets folder and the output defaults to exports/<course name>.
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let course_path = match args.first() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: bevy-golf-export <course> [output]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let course = match Course::load_file(asset_file(course_path)) {
+        Ok(course) => course,
+        Err(err) => {
+            eprintln!("Failed to load course {}: {}", course_path, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let defs = match load_tile_packs_from(&asset_file("")) {
+        Ok((defs, _)) => defs,
+        Err(err) => {
+            eprintln!("Failed to load the tile packs: {:#}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let output = match args.get(1) {
+        Some(output) => PathBuf::from(output),
+        None => PathBuf::from("exports").join(export_name(&course.name)),
+    };
+    match write_all(&course_meshes(&course.tiles, &defs), &output) {
+        Ok(()) => {
+            println!("Exported {} to {}", course.name, output.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to export {}: {}", course.name, err);
+            ExitCode::FAILURE
+        }
+    }
+}
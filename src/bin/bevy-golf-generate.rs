@@ -0,0 +1,89 @@
+use std::{process::ExitCode, str::FromStr};
+
+use bevy_golf::{
+    headless::asset_file,
+    proc::{
+        generator::{daily_seed, generate_course, GeneratorSettings},
+        load_tile_packs_from,
+    },
+    validate::validate_course,
+};
+
+const USAGE: &str = "Usage: bevy-golf-generate <seed|daily> <output.course.ron> [--length <tiles>] [--turns <count>] [--elevation <count>] [--hazards <density>]";
+
+/// Generates a hole from a seed, the same seed and settings always make the same file.
+/// Using "daily" as the seed makes today's hole.
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+
+    let seed = match args[0].as_str() {
+        "daily" => daily_seed(),
+        seed => match seed.parse() {
+            Ok(seed) => seed,
+            Err(_) => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let output = &args[1];
+
+    let mut settings = GeneratorSettings::default();
+    let parsed = parse_flag(&args, "--length", &mut settings.length)
+        && parse_flag(&args, "--turns", &mut settings.turns)
+        && parse_flag(&args, "--elevation", &mut settings.elevation_changes)
+        && parse_flag(&args, "--hazards", &mut settings.hazard_density);
+    if !parsed || !(0.0..=1.0).contains(&settings.hazard_density) {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+
+    let defs = match load_tile_packs_from(&asset_file("")) {
+        Ok((defs, _)) => defs,
+        Err(err) => {
+            eprintln!("Failed to load the tile packs: {:#}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let course = match generate_course(seed, &settings, &defs) {
+        Ok(course) => course,
+        Err(err) => {
+            eprintln!("Failed to generate a hole from seed {}: {:#}", seed, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    for problem in validate_course(&course, &defs) {
+        println!("{}", problem);
+    }
+
+    match course.save_file(output) {
+        Ok(()) => {
+            println!("Wrote {} to {}", course.name, output);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write {}: {}", output, err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Sets a value from the argument after a flag, returning false when it can't be parsed.
+fn parse_flag<T: FromStr>(args: &[String], flag: &str, value: &mut T) -> bool {
+    let index = match args.iter().position(|arg| arg == flag) {
+        Some(index) => index,
+        None => return true,
+    };
+    match args.get(index + 1).map(|arg| arg.parse()) {
+        Some(Ok(parsed)) => {
+            *value = parsed;
+            true
+        }
+        _ => false,
+    }
+}
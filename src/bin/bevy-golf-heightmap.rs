@@ -0,0 +1,88 @@
+use std::{path::Path, process::ExitCode};
+
+use bevy_golf::{
+    headless::asset_file,
+    proc::{
+        heightmap::{import_heightmap_files, DEFAULT_LEVELS},
+        load_tile_packs_from,
+    },
+    validate::validate_course,
+};
+
+const USAGE: &str =
+    "Usage: bevy-golf-heightmap <heightmap.png> <output.course.ron> [--mask <mask.png>] [--levels <steps>]";
+
+/// Builds a course from a greyscale heightmap.
+/// The mask marks empty cells in black, the cup in red, the tee in green and hazards in blue.
+/// Levels is how many steps high white is, it defaults to DEFAULT_LEVELS.
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    // The paths are the arguments that aren't flags or their values.
+    let paths: Vec<&String> = args
+        .iter()
+        .enumerate()
+        .filter(|(index, arg)| {
+            !arg.starts_with("--") && (*index == 0 || !args[index - 1].starts_with("--"))
+        })
+        .map(|(_, arg)| arg)
+        .collect();
+    let (heightmap, output) = match paths[..] {
+        [heightmap, output] => (heightmap, output),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let levels = match flag("--levels").map(|levels| levels.parse()) {
+        Some(Ok(levels)) => levels,
+        Some(Err(_)) => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+        None => DEFAULT_LEVELS,
+    };
+
+    let defs = match load_tile_packs_from(&asset_file("")) {
+        Ok((defs, _)) => defs,
+        Err(err) => {
+            eprintln!("Failed to load the tile packs: {:#}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mask = flag("--mask").map(Path::new);
+    let course = match import_heightmap_files(Path::new(heightmap), mask, levels, &defs) {
+        Ok(course) => course,
+        Err(err) => {
+            eprintln!("Failed to import {}: {:#}", heightmap, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Point out anything the designer needs to fix in the images.
+    for problem in validate_course(&course, &defs) {
+        println!("{}", problem);
+    }
+
+    match course.save_file(output) {
+        Ok(()) => {
+            println!(
+                "Wrote {} tiles from {} to {}",
+                course.tiles.len(),
+                heightmap,
+                output
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write {}: {}", output, err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use bevy_golf::{
+    headless::asset_file,
+    proc::migrate::{
+        read_course, read_tile_pack, save_tile_pack, COURSE_VERSION, TILE_PACK_VERSION,
+    },
+};
+
+/// Rewrites course and tile pack files in place with the latest version of their format.
+/// Usage: bevy-golf-migrate [files...] [--check]
+/// Without any files every course and tile pack in the assets folder is migrated.
+/// With --check nothing is written, and it fails when a file needs migrating.
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let check = args.iter().any(|arg| arg == "--check");
+    let mut files: Vec<PathBuf> = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .map(PathBuf::from)
+        .collect();
+
+    if files.is_empty() {
+        files.extend(files_in(&asset_file("courses"), ".course.ron"));
+        files.extend(files_in(&asset_file("tiles"), ".ron"));
+    }
+
+    let mut failed = false;
+    let mut outdated = false;
+    for file in files {
+        match migrate_file(&file, check) {
+            Ok(Some(from_version)) => {
+                outdated = true;
+                let action = match check {
+                    true => "needs migrating",
+                    false => "migrated",
+                };
+                println!(
+                    "{} {} from version {}",
+                    file.display(),
+                    action,
+                    from_version
+                );
+            }
+            Ok(None) => println!("{} is up to date", file.display()),
+            Err(err) => {
+                failed = true;
+                eprintln!("Failed to migrate {}: {:#}", file.display(), err);
+            }
+        }
+    }
+
+    if failed || (check && outdated) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Upgrades a file, returning the version it was upgraded from if it was outdated.
+fn migrate_file(file: &Path, check: bool) -> anyhow::Result<Option<u32>> {
+    let bytes = fs::read(file)?;
+
+    if file.to_string_lossy().ends_with(".course.ron") {
+        let course = read_course(&bytes)?;
+        if !course.outdated(COURSE_VERSION) {
+            return Ok(None);
+        }
+        if !check {
+            course.value.save_file(file)?;
+        }
+        Ok(Some(course.from_version))
+    } else {
+        let pack = read_tile_pack(&bytes)?;
+        if !pack.outdated(TILE_PACK_VERSION) {
+            return Ok(None);
+        }
+        if !check {
+            save_tile_pack(&pack.value, file)?;
+        }
+        Ok(Some(pack.from_version))
+    }
+}
+
+/// Gets the files in a folder whose names end with a suffix, sorted so the output is always in the same order.
+fn files_in(folder: &Path, suffix: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.to_string_lossy().ends_with(suffix))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
@@ -0,0 +1,55 @@
+use std::process::ExitCode;
+
+use bevy_golf::{headless::asset_file, par::estimate_par, proc::course::Course};
+
+/// Suggests a par for a course and writes it into the course file.
+/// Usage: bevy-golf-par <course> [--dry-run]
+/// The course is relative to the assets folder, like "courses/first.course.ron".
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let course_path = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: bevy-golf-par <course> [--dry-run]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+
+    let estimate = match estimate_par(course_path) {
+        Ok(estimate) => estimate,
+        Err(err) => {
+            eprintln!("Failed to estimate par for {}: {}", course_path, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!(
+        "Route: {} tiles, {:.1} long ({} shots)",
+        estimate.route.tiles.len(),
+        estimate.route.length,
+        estimate.route_par
+    );
+    println!("Simulated: {:.1} shots", estimate.simulated_shots);
+    println!("Par: {}", estimate.par);
+
+    if dry_run {
+        return ExitCode::SUCCESS;
+    }
+
+    let file = asset_file(course_path);
+    let result = Course::load_file(&file).and_then(|mut course| {
+        course.par = Some(estimate.par);
+        course.save_file(&file)
+    });
+    match result {
+        Ok(()) => {
+            println!("Wrote par to {}", file.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write par to {}: {}", file.display(), err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{path::Path, process::ExitCode};
+
+use bevy_golf::{
+    headless::asset_file,
+    proc::{course::Course, load_tile_packs_from},
+    validate::{validate_course, Severity},
+};
+
+/// The course is valid.
+const VALID: u8 = 0;
+/// The course has errors, or warnings when --strict is used.
+const INVALID: u8 = 1;
+/// The course or the tile packs couldn't be loaded.
+const FAILED: u8 = 2;
+
+/// Checks a course for problems without opening a window.
+/// Usage: bevy-golf-validate <course> [--strict]
+/// The course is a file path, or a path relative to the assets folder.
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let course_path = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: bevy-golf-validate <course> [--strict]");
+            return ExitCode::from(FAILED);
+        }
+    };
+    let strict = args.iter().any(|arg| arg == "--strict");
+
+    let file = match Path::new(course_path).exists() {
+        true => Path::new(course_path).to_path_buf(),
+        false => asset_file(course_path),
+    };
+    let course = match Course::load_file(&file) {
+        Ok(course) => course,
+        Err(err) => {
+            eprintln!("Failed to load course {}: {}", file.display(), err);
+            return ExitCode::from(FAILED);
+        }
+    };
+
+    let (defs, conflicts) = match load_tile_packs_from(&asset_file("")) {
+        Ok(packs) => packs,
+        Err(err) => {
+            eprintln!("Failed to load the tile packs: {:#}", err);
+            return ExitCode::from(FAILED);
+        }
+    };
+    for conflict in conflicts {
+        println!("warning: {}", conflict);
+    }
+
+    let problems = validate_course(&course, &defs);
+    for problem in problems.iter() {
+        println!("{}", problem);
+    }
+
+    let worst = problems.iter().map(|problem| problem.severity).max();
+    let invalid = match worst {
+        Some(Severity::Error) => true,
+        Some(Severity::Warning) => strict,
+        None => false,
+    };
+    if invalid {
+        println!("{} is invalid", file.display());
+        ExitCode::from(INVALID)
+    } else {
+        println!("{} is valid", file.display());
+        ExitCode::from(VALID)
+    }
+}
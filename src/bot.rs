@@ -0,0 +1,328 @@
+use std::{
+    f32::consts::TAU,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+use anyhow::anyhow;
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::ball::{shot_aim, ApplyShots, Ball, Player, ShootEvent, Turn, MIN_VELOCITY};
+use crate::headless::Simulation;
+use crate::proc::{course::Course, in_cup, CurrentCourse};
+use crate::replay::Playback;
+
+/// How many directions are tried around the ball before refining the best one.
+const SAMPLE_DIRECTIONS: usize = 16;
+/// How many powers are tried in each direction.
+const SAMPLE_POWERS: usize = 5;
+/// How many times the best shot is refined, halving the search step each time.
+const REFINE_ROUNDS: usize = 6;
+
+pub struct BotPlugin;
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bots>();
+        app.init_resource::<BotPlanner>();
+        app.add_system(send_courses_to_planner);
+        app.add_system(plan_bot_shots.after(send_courses_to_planner));
+        app.add_system(take_bot_shots.before(ApplyShots));
+    }
+}
+
+/// The players that are played by bots, and how well they play.
+#[derive(Default)]
+pub struct Bots(pub HashMap<usize, Difficulty>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// How far (in radians) a bot's aim can be off either side.
+    fn aim_noise(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.2,
+            Difficulty::Normal => 0.08,
+            Difficulty::Hard => 0.02,
+        }
+    }
+
+    /// How far a bot's power can be off, as a fraction of the power.
+    fn power_noise(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.25,
+            Difficulty::Normal => 0.1,
+            Difficulty::Hard => 0.03,
+        }
+    }
+
+    /// Makes a shot a bit worse, the easier the bot the worse it gets.
+    pub fn add_noise(&self, aim: Vec3, rng: &mut impl Rng) -> Vec3 {
+        let angle = rng.gen_range(-self.aim_noise()..=self.aim_noise());
+        let power = 1.0 + rng.gen_range(-self.power_noise()..=self.power_noise());
+        let aim = Quat::from_rotation_y(angle) * aim * power;
+        aim.clamp_length_max(1.0)
+    }
+}
+
+/// Marks a ball whose bot is working out it's shot.
+#[derive(Component)]
+pub struct BotPlan;
+
+/// Works out the bots' shots on it's own thread, so the game keeps running while they think.
+/// The thread keeps a simulation of the course, it's only rebuilt when the course changes.
+pub struct BotPlanner {
+    requests: Sender<PlanRequest>,
+    plans: Mutex<Receiver<Plan>>,
+}
+
+enum PlanRequest {
+    /// The course changed, shots are planned on this course from now on.
+    Course(Course),
+    Shot {
+        ball: Entity,
+        player: usize,
+        start: Vec3,
+    },
+}
+
+/// A shot the planner worked out.
+struct Plan {
+    ball: Entity,
+    player: usize,
+    aim: anyhow::Result<Vec3>,
+}
+
+impl Default for BotPlanner {
+    fn default() -> Self {
+        let (requests, planner_requests) = mpsc::channel();
+        let (planner_plans, plans) = mpsc::channel();
+        thread::Builder::new()
+            .name("bot planner".to_string())
+            .spawn(move || run_planner(planner_requests, planner_plans))
+            .expect("Failed to start the bot planner");
+
+        BotPlanner {
+            requests,
+            plans: Mutex::new(plans),
+        }
+    }
+}
+
+/// Plans shots until the game closes.
+/// Simulations hold an App which has to stay on the thread it was made on, so it lives here.
+fn run_planner(requests: Receiver<PlanRequest>, plans: Sender<Plan>) {
+    let mut course = None;
+    let mut simulation = None;
+
+    for request in requests.iter() {
+        match request {
+            PlanRequest::Course(new_course) => {
+                course = Some(new_course);
+                simulation = None;
+            }
+            PlanRequest::Shot {
+                ball,
+                player,
+                start,
+            } => {
+                // The simulation is built for the first shot on the course, so edits don't rebuild it over and over.
+                if simulation.is_none() {
+                    simulation = match &course {
+                        Some(course) => Simulation::from_course(course.clone())
+                            .map_err(|err| error!("Bots can't simulate the course: {:#}", err))
+                            .ok(),
+                        None => None,
+                    };
+                }
+                let aim = match &mut simulation {
+                    Some(simulation) => plan_shot(simulation, player, start),
+                    None => Err(anyhow!("There's no course to simulate")),
+                };
+
+                // The game has closed.
+                if plans.send(Plan { ball, player, aim }).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Finds the shot that gets the ball closest to the cup, by trying shots in a simulation of the course.
+/// Shots are sampled all around the ball, then the best is refined by trying shots either side of it.
+pub fn plan_shot(simulation: &mut Simulation, player: usize, start: Vec3) -> anyhow::Result<Vec3> {
+    let cup = simulation
+        .cup()
+        .ok_or_else(|| anyhow::anyhow!("The course doesn't have a cup"))?;
+
+    // How close a shot gets to the cup, lower is better.
+    let mut score = |angle: f32, power: f32| {
+        let ball = simulation.place_ball(player, start);
+        let result = simulation.shoot(ball, shot_aim(angle, power));
+        if result.out_of_bounds {
+            f32::INFINITY
+        } else if in_cup(result.position, cup) {
+            0.0
+        } else {
+            result.position.distance(cup)
+        }
+    };
+
+    // Start by aiming straight at the cup.
+    let to_cup = cup - start;
+    let cup_angle = f32::atan2(-to_cup.x, -to_cup.z);
+
+    let mut best = (f32::INFINITY, cup_angle, 1.0);
+    for direction in 0..SAMPLE_DIRECTIONS {
+        let angle = cup_angle + TAU * direction as f32 / SAMPLE_DIRECTIONS as f32;
+        for power in 1..=SAMPLE_POWERS {
+            let power = power as f32 / SAMPLE_POWERS as f32;
+            let distance = score(angle, power);
+            if distance < best.0 {
+                best = (distance, angle, power);
+            }
+        }
+    }
+
+    let mut angle_step = TAU / SAMPLE_DIRECTIONS as f32 * 0.5;
+    let mut power_step = 1.0 / SAMPLE_POWERS as f32 * 0.5;
+    for _ in 0..REFINE_ROUNDS {
+        let (_, angle, power) = best;
+        let candidates = [
+            (angle - angle_step, power),
+            (angle + angle_step, power),
+            (angle, (power - power_step).max(0.0)),
+            (angle, (power + power_step).min(1.0)),
+        ];
+        for (angle, power) in candidates {
+            let distance = score(angle, power);
+            if distance < best.0 {
+                best = (distance, angle, power);
+            }
+        }
+        angle_step *= 0.5;
+        power_step *= 0.5;
+    }
+
+    let (distance, angle, power) = best;
+    info!(
+        "Player {} plans a shot at {:.2} radians and {:.0}% power, ending {:.2} from the cup",
+        player,
+        angle,
+        power * 100.0,
+        distance
+    );
+    Ok(shot_aim(angle, power))
+}
+
+/// Gives the planner the current course whenever it loads or changes.
+fn send_courses_to_planner(
+    mut ev_assets: EventReader<AssetEvent<Course>>,
+    current: Res<CurrentCourse>,
+    courses: Res<Assets<Course>>,
+    planner: Res<BotPlanner>,
+) {
+    for ev in ev_assets.iter() {
+        let handle = match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle: _ } => continue,
+        };
+        if *handle != current.handle {
+            continue;
+        }
+        if let Some(course) = courses.get(handle) {
+            planner
+                .requests
+                .send(PlanRequest::Course(course.clone()))
+                .ok();
+        }
+    }
+}
+
+/// Starts working out a bot's shot when it's their turn.
+fn plan_bot_shots(
+    mut commands: Commands,
+    planner: Res<BotPlanner>,
+    bots: Res<Bots>,
+    turn: Res<Turn>,
+    playback: Option<Res<Playback>>,
+    balls: Query<(Entity, &Player, &Velocity, &Transform), (With<Ball>, Without<BotPlan>)>,
+) {
+    // The replay already has the bots' shots.
+    if playback.is_some() || !turn.ready() || !bots.0.contains_key(&turn.player) {
+        return;
+    }
+
+    for (ent, player, velocity, transform) in balls.iter() {
+        if player.0 != turn.player || velocity.linvel.length_squared() >= MIN_VELOCITY {
+            continue;
+        }
+
+        let request = PlanRequest::Shot {
+            ball: ent,
+            player: player.0,
+            start: transform.translation,
+        };
+        if planner.requests.send(request).is_ok() {
+            commands.entity(ent).insert(BotPlan);
+        }
+    }
+}
+
+/// Takes a bot's shot once it has been worked out, through the same ShootEvent the players use.
+fn take_bot_shots(
+    mut commands: Commands,
+    mut ev_shoot: EventWriter<ShootEvent>,
+    bots: Res<Bots>,
+    planner: Res<BotPlanner>,
+    plans: Query<(), With<BotPlan>>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for plan in planner.plans.lock().unwrap().try_iter() {
+        // The ball may have gone, like when the replay took over.
+        if plans.get(plan.ball).is_err() {
+            continue;
+        }
+        commands.entity(plan.ball).remove::<BotPlan>();
+
+        let difficulty = bots
+            .0
+            .get(&plan.player)
+            .copied()
+            .unwrap_or(Difficulty::Normal);
+        match plan.aim {
+            Ok(aim) => ev_shoot.send(ShootEvent {
+                ball: plan.ball,
+                aim: difficulty.add_noise(aim, &mut rng),
+            }),
+            Err(err) => {
+                error!("Player {} couldn't plan a shot: {}", plan.player, err);
+                // Pass so the round doesn't get stuck on this bot.
+                ev_shoot.send(ShootEvent {
+                    ball: plan.ball,
+                    aim: Vec3::ZERO,
+                });
+            }
+        }
+    }
+}
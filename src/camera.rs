@@ -1,138 +1,684 @@
 use bevy::{
+    input::{mouse::MouseMotion, touch::Touches},
     math::const_vec3,
     prelude::*,
     render::camera::{Camera3d, CameraProjection, WindowOrigin},
 };
 
+use crate::ball::{Player, Turn};
+use crate::input::{Action, ActionState};
+use crate::proc::{
+    course::Course,
+    find_cup,
+    tile::{Tile, TILE_BOUNDS},
+    CurrentCourse, NewHoleEvent, TileRegistry,
+};
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<CameraOrbit>();
+        app.init_resource::<CameraZoom>();
+        app.init_resource::<CameraDirector>();
         app.add_startup_system(add_camera);
-        app.add_system(camera_follow);
+        app.add_system(orbit_camera);
+        app.add_system(zoom_camera);
+        app.add_system(pan_camera);
+        app.add_system(toggle_projection);
+        app.add_system(switch_camera_mode);
+        app.add_system(direct_camera.after(switch_camera_mode).after(zoom_camera));
+        app.add_system(
+            camera_follow
+                .after(orbit_camera)
+                .after(direct_camera)
+                .after(pan_camera),
+        );
+        app.add_system(fly_camera);
     }
 }
 
 #[derive(Component)]
 pub struct CameraTarget;
 
+/// Eases a value towards a target over time.
 #[derive(Component, Debug)]
-pub struct Smoother<T> {
+pub struct Smoother<T: Smooth> {
     pub smoothness: f32,
     pub enabled: bool,
     pub last_value: T,
 }
+
+impl<T: Smooth> Smoother<T> {
+    /// Moves towards the target, returning the new value.
+    pub fn update(&mut self, target: T, delta_seconds: f32) -> T {
+        self.last_value = match self.enabled {
+            true => self
+                .last_value
+                .smooth(target, (self.smoothness * delta_seconds).min(1.0)),
+            false => target,
+        };
+        self.last_value
+    }
+}
+
+/// Values a Smoother can ease between.
+pub trait Smooth: Copy + Send + Sync + 'static {
+    /// Gets the value an amount (from 0 to 1) of the way to the target.
+    fn smooth(self, target: Self, amount: f32) -> Self;
+}
+
+impl Smooth for Vec3 {
+    fn smooth(self, target: Self, amount: f32) -> Self {
+        self.lerp(target, amount)
+    }
+}
+
+impl Smooth for Quat {
+    fn smooth(self, target: Self, amount: f32) -> Self {
+        self.slerp(target, amount)
+    }
+}
+
+impl Smooth for f32 {
+    fn smooth(self, target: Self, amount: f32) -> Self {
+        self + (target - self) * amount
+    }
+}
+
 const CAMERA_OFFSET: Vec3 = const_vec3!([-7.0, 7.0, -7.0]);
+const CAMERA_FOV: f32 = 15.0;
+/// The orthographic scale that shows about as much of the course as the perspective camera.
+const ORTHOGRAPHIC_SCALE: f32 = 1.6;
+/// How far the camera can zoom in and out, as a multiple of the starting view.
+const MIN_ZOOM: f32 = 0.4;
+const MAX_ZOOM: f32 = 3.0;
+/// How much each line of scrolling, or each press of ZoomIn and ZoomOut, zooms.
+const SCROLL_ZOOM: f32 = 0.1;
+/// Roughly how much of the course fits on the screen at the starting zoom, the height of the orthographic view.
+const VIEW_HEIGHT: f32 = ORTHOGRAPHIC_SCALE * 2.0;
+/// The overview leaves this much room around the hole.
+const OVERVIEW_MARGIN: f32 = 1.2;
+/// How long the flyover takes to get from the tee to the cup.
+const FLYOVER_SECONDS: f32 = 3.0;
+/// How long the camera takes to blend from one mode to the next.
+const MODE_BLEND_SECONDS: f32 = 0.75;
+/// How fast the free camera flies, in units per second.
+const FREE_SPEED: f32 = 4.0;
+/// How far the free camera turns for each pixel the mouse moves, in radians.
+const FREE_LOOK_SPEED: f32 = 0.004;
+
+/// What the camera director is pointing the camera at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Follows the ball of the player whose turn it is.
+    Follow,
+    /// Frames the whole hole.
+    Overview,
+    /// Flies from the tee to the cup, this plays at the start of each hole.
+    Flyover,
+    /// Flies around with WASD, looking around while the right mouse button is held. For debugging.
+    Free,
+}
+
+/// Decides what the camera looks at. Switch modes with 1 to 4, space or clicking skips the flyover.
+pub struct CameraDirector {
+    pub mode: CameraMode,
+    /// Where the camera should be looking, the camera eases towards it.
+    pub focus: Vec3,
+    /// How far the camera should be zoomed out, this is the CameraZoom unless the mode needs to fit something on screen.
+    pub zoom: f32,
+    /// How far the camera has been dragged from the focus with two fingers.
+    /// It's cleared when the mode changes or a new hole starts.
+    pub pan: Vec3,
+    /// Whether the flyover was skipped this frame, so the press that skipped it can be ignored.
+    pub skipped_flyover: bool,
+    /// How long the flyover has been playing.
+    flyover_time: f32,
+}
+
+impl Default for CameraDirector {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::Follow,
+            focus: Vec3::ZERO,
+            zoom: 1.0,
+            pan: Vec3::ZERO,
+            skipped_flyover: false,
+            flyover_time: 0.0,
+        }
+    }
+}
+
+impl CameraDirector {
+    /// Plays the flyover from the start.
+    pub fn start_flyover(&mut self) {
+        self.mode = CameraMode::Flyover;
+        self.flyover_time = 0.0;
+    }
+}
+
+/// Blends the camera from where it was when the director changed modes, so it never jumps.
+#[derive(Component)]
+struct ModeBlend {
+    mode: CameraMode,
+    from: Transform,
+    progress: f32,
+}
+
+impl ModeBlend {
+    /// Starts blending from the current transform whenever the mode changes, then moves the blend along.
+    fn update(
+        &mut self,
+        mode: CameraMode,
+        current: &Transform,
+        target: Transform,
+        delta_seconds: f32,
+    ) -> Transform {
+        if mode != self.mode {
+            self.mode = mode;
+            self.from = *current;
+            self.progress = 0.0;
+        }
+        self.progress = (self.progress + delta_seconds / MODE_BLEND_SECONDS).min(1.0);
+
+        let amount = ease(self.progress);
+        Transform {
+            translation: self.from.translation.lerp(target.translation, amount),
+            rotation: self.from.rotation.slerp(target.rotation, amount),
+            scale: target.scale,
+        }
+    }
+}
+
+/// Eases in and out of a value from 0 to 1.
+fn ease(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// How far the camera has been turned around it's target, in quarter turns.
+#[derive(Default, Debug)]
+pub struct CameraOrbit {
+    pub quarter_turns: i32,
+}
+
+impl CameraOrbit {
+    /// Gets the rotation around the target.
+    /// Directions on the screen turn with the camera, so aim controls should be rotated by this.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(std::f32::consts::FRAC_PI_2 * self.quarter_turns as f32)
+    }
+
+    /// Turns a direction on the screen (up is +Y) into a direction across the ground.
+    pub fn screen_direction(&self, screen: Vec2) -> Vec3 {
+        let away = self.rotation() * -CAMERA_OFFSET;
+        let forward = Vec3::new(away.x, 0.0, away.z).normalize();
+        let right = forward.cross(Vec3::Y);
+        forward * screen.y + right * screen.x
+    }
+}
+
+/// How far the camera is zoomed out, the view is this many times bigger than at the start.
+#[derive(Debug)]
+pub struct CameraZoom {
+    pub zoom: f32,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self { zoom: 1.0 }
+    }
+}
+
+impl CameraZoom {
+    /// Zooms in (less than 1) or out (more than 1), staying within the limits.
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+fn perspective_projection() -> PerspectiveProjection {
+    PerspectiveProjection {
+        fov: f32::to_radians(CAMERA_FOV),
+        ..default()
+    }
+}
+
+fn orthographic_projection() -> OrthographicProjection {
+    let mut projection = OrthographicCameraBundle::new_3d().orthographic_projection;
+    projection.scale = ORTHOGRAPHIC_SCALE;
+    projection
+}
 
 fn add_camera(mut commands: Commands) {
-    // let mut camera = OrthographicCameraBundle::new_3d();
-    // camera.orthographic_projection.scale = 2.0;
     let mut camera = PerspectiveCameraBundle::new_3d();
-    camera.perspective_projection.fov = f32::to_radians(15.0);
+    camera.perspective_projection = perspective_projection();
     camera.transform = Transform::from_translation(CAMERA_OFFSET).looking_at(Vec3::ZERO, Vec3::Y);
+    let blend = ModeBlend {
+        mode: CameraMode::Follow,
+        from: camera.transform,
+        progress: 1.0,
+    };
+
+    commands
+        .spawn_bundle(camera)
+        .insert(blend)
+        .insert(Smoother {
+            smoothness: 6.0,
+            enabled: true,
+            last_value: Vec3::ZERO,
+        })
+        .insert(Smoother {
+            smoothness: 8.0,
+            enabled: true,
+            last_value: Quat::IDENTITY,
+        })
+        .insert(Smoother {
+            smoothness: 10.0,
+            enabled: true,
+            last_value: 1.0,
+        });
+}
+
+/// Zooms the camera with ZoomIn and ZoomOut, or by pinching with two fingers.
+fn zoom_camera(actions: Res<ActionState>, touches: Res<Touches>, mut zoom: ResMut<CameraZoom>) {
+    // Pressing a button zooms as much as scrolling a line.
+    let lines = |action| actions.scrolled(action) + actions.just_pressed(action) as u8 as f32;
+    let amount = (lines(Action::ZoomIn) - lines(Action::ZoomOut)) * SCROLL_ZOOM;
+    if amount != 0.0 {
+        // Each line zooms by the same factor however many are scrolled at once, so fast scrolling can't flip the zoom.
+        zoom.zoom_by((-amount).exp());
+    }
+
+    let fingers: Vec<_> = touches.iter().collect();
+    if let [a, b] = fingers[..] {
+        let distance = a.position().distance(b.position());
+        let previous = a.previous_position().distance(b.previous_position());
+        if distance > 0.0 && previous > 0.0 {
+            // Spreading the fingers apart zooms in.
+            zoom.zoom_by(previous / distance);
+        }
+    }
+}
+
+/// Moves the camera around by dragging with two fingers.
+fn pan_camera(
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    orbit: Res<CameraOrbit>,
+    zoom: Res<CameraZoom>,
+    mut director: ResMut<CameraDirector>,
+) {
+    let fingers: Vec<_> = touches.iter().collect();
+    let (a, b) = match fingers[..] {
+        [a, b] => (a, b),
+        _ => return,
+    };
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    // Move the camera the other way, so the course follows the fingers.
+    let middle = (a.position() + b.position()) * 0.5;
+    let last_middle = (a.previous_position() + b.previous_position()) * 0.5;
+    // This is roughly how far across the ground each pixel is.
+    let scale = VIEW_HEIGHT * zoom.zoom / window.height();
+    director.pan -= orbit.screen_direction(middle - last_middle) * scale;
+}
+
+/// Switches the camera between perspective and orthographic with ToggleProjection.
+fn toggle_projection(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    cameras: Query<(Entity, Option<&PerspectiveProjection>), With<Camera3d>>,
+) {
+    if !actions.just_pressed(Action::ToggleProjection) {
+        return;
+    }
 
-    commands.spawn_bundle(camera).insert(Smoother {
-        smoothness: 6.0,
-        enabled: true,
-        last_value: CAMERA_OFFSET,
+    // The camera's projection matrix is rebuilt when the new projection is added.
+    for (ent, perspective) in cameras.iter() {
+        match perspective {
+            Some(_) => {
+                commands
+                    .entity(ent)
+                    .remove::<PerspectiveProjection>()
+                    .insert(orthographic_projection());
+            }
+            None => {
+                commands
+                    .entity(ent)
+                    .remove::<OrthographicProjection>()
+                    .insert(perspective_projection());
+            }
+        }
+    }
+}
+
+/// Turns the camera around it's target a quarter turn at a time with RotateCameraLeft and RotateCameraRight.
+/// The free camera has no target, and flies with the same keys instead.
+fn orbit_camera(
+    actions: Res<ActionState>,
+    director: Res<CameraDirector>,
+    mut orbit: ResMut<CameraOrbit>,
+) {
+    if director.mode == CameraMode::Free {
+        return;
+    }
+    if actions.just_pressed(Action::RotateCameraLeft) {
+        orbit.quarter_turns -= 1;
+    }
+    if actions.just_pressed(Action::RotateCameraRight) {
+        orbit.quarter_turns += 1;
+    }
+    orbit.quarter_turns = orbit.quarter_turns.rem_euclid(4);
+}
+
+/// Switches the director's mode with the camera actions, and plays the flyover when a different hole starts.
+fn switch_camera_mode(
+    actions: Res<ActionState>,
+    touches: Res<Touches>,
+    mut ev_new_hole: EventReader<NewHoleEvent>,
+    mut director: ResMut<CameraDirector>,
+    course: Option<Res<CurrentCourse>>,
+    courses: Res<Assets<Course>>,
+    mut last_hole: Local<Option<(Handle<Course>, String)>>,
+) {
+    let last_mode = director.mode;
+    if ev_new_hole.iter().count() > 0 {
+        // Editing, pasting over or reloading the hole restarts it too, but the camera stays with the player.
+        let hole = course.and_then(|current| {
+            let course = courses.get(&current.handle)?;
+            Some((current.handle.clone(), course.name.clone()))
+        });
+        if hole != *last_hole {
+            *last_hole = hole;
+            director.start_flyover();
+            director.pan = Vec3::ZERO;
+        }
+    }
+
+    let skip =
+        actions.just_pressed(Action::SkipFlyover) || touches.iter_just_pressed().next().is_some();
+    director.skipped_flyover = director.mode == CameraMode::Flyover && skip;
+    if director.skipped_flyover {
+        director.mode = CameraMode::Follow;
+    }
+
+    if actions.just_pressed(Action::FollowCamera) {
+        director.mode = CameraMode::Follow;
+    }
+    if actions.just_pressed(Action::OverviewCamera) {
+        director.mode = CameraMode::Overview;
+    }
+    if actions.just_pressed(Action::FlyoverCamera) {
+        director.start_flyover();
+    }
+    if actions.just_pressed(Action::FreeCamera) {
+        director.mode = CameraMode::Free;
+    }
+
+    if director.mode != last_mode {
+        director.pan = Vec3::ZERO;
+    }
+}
+
+/// Works out where the camera should be looking for the director's mode.
+fn direct_camera(
+    mut director: ResMut<CameraDirector>,
+    targets: Query<(&Transform, Option<&Player>), With<CameraTarget>>,
+    tiles: Query<&Tile>,
+    registry: Res<TileRegistry>,
+    course: Option<Res<CurrentCourse>>,
+    turn: Res<Turn>,
+    zoom: Res<CameraZoom>,
+    time: Res<Time>,
+) {
+    let mode = director.mode;
+    match mode {
+        CameraMode::Follow => {
+            // Stay put until there's something to follow, the balls are only added once the course loads.
+            if let Some(focus) = follow_focus(&targets, turn.player) {
+                director.focus = focus;
+            }
+            director.zoom = zoom.zoom;
+        }
+        CameraMode::Overview => {
+            if let Some((focus, zoom)) = overview(tiles.iter()) {
+                director.focus = focus;
+                director.zoom = zoom;
+            }
+        }
+        CameraMode::Flyover => {
+            director.flyover_time += time.delta_seconds();
+            let tee = course.and_then(|course| course.tee);
+            let cup = find_cup(tiles.iter(), &registry);
+            match (tee, cup) {
+                (Some(tee), Some(cup)) if director.flyover_time < FLYOVER_SECONDS => {
+                    let progress = ease(director.flyover_time / FLYOVER_SECONDS);
+                    director.focus = tee.world_position().lerp(cup, progress);
+                    director.zoom = zoom.zoom;
+                }
+                // Holes without a cup have nothing to fly to.
+                _ => director.mode = CameraMode::Follow,
+            }
+        }
+        CameraMode::Free => {}
+    }
+}
+
+/// Gets the position of the ball whose turn it is, ignoring the Y axis.
+/// Targets that don't belong to a player are all followed together.
+fn follow_focus(
+    targets: &Query<(&Transform, Option<&Player>), With<CameraTarget>>,
+    player: usize,
+) -> Option<Vec3> {
+    let active = targets
+        .iter()
+        .find(|(_, target_player)| target_player.map_or(false, |p| p.0 == player));
+    let mut focus = match active {
+        Some((transform, _)) => transform.translation,
+        None => {
+            let count = targets.iter().count();
+            if count == 0 {
+                return None;
+            }
+            targets.iter().fold(Vec3::ZERO, |sum, (transform, _)| {
+                sum + transform.translation
+            }) / count as f32
+        }
+    };
+    focus.y = 0.0;
+    Some(focus)
+}
+
+/// Gets the middle of the hole and the zoom that fits all of it's tiles on the screen.
+fn overview<'a>(mut tiles: impl Iterator<Item = &'a Tile>) -> Option<(Vec3, f32)> {
+    let first = tiles.next()?.position.as_vec3() * TILE_BOUNDS;
+    let (min, max) = tiles.fold((first, first), |(min, max), tile| {
+        let position = tile.position.as_vec3() * TILE_BOUNDS;
+        (min.min(position), max.max(position))
     });
+
+    // Tile positions are at their centres, so half a tile is added to each side.
+    let size = max - min + TILE_BOUNDS;
+    let width = Vec2::new(size.x, size.z).length();
+    let zoom = (width * OVERVIEW_MARGIN / VIEW_HEIGHT).max(MIN_ZOOM);
+    Some(((min + max) * 0.5, zoom))
 }
 
+/// Eases the camera towards the director's focus and zoom, orbiting around it.
 fn camera_follow(
-    mut transforms: ParamSet<(
-        Query<(&mut Transform, Option<&mut Smoother<Vec3>>), With<Camera3d>>,
-        Query<&Transform, With<CameraTarget>>,
-    )>,
+    mut cameras: Query<
+        (
+            &mut Transform,
+            Option<&mut Smoother<Vec3>>,
+            Option<&mut Smoother<Quat>>,
+            Option<&mut Smoother<f32>>,
+            Option<&mut OrthographicProjection>,
+            Option<&mut ModeBlend>,
+        ),
+        With<Camera3d>,
+    >,
+    director: Res<CameraDirector>,
+    orbit: Res<CameraOrbit>,
     time: Res<Time>,
 ) {
-    // Our target position is the middle of all Camera targets ignoring the Y axis.
-    let mut target_pos = Vec3::ZERO;
-    for target_transform in transforms.p1().iter() {
-        target_pos += target_transform.translation;
-    }
-    target_pos /= transforms.p1().iter().count() as f32;
-    target_pos.y = 0.0;
-    target_pos += CAMERA_OFFSET;
-
-    for (mut transform, smoother) in transforms.p0().iter_mut() {
-        match smoother {
-            Some(mut smoother) => {
-                if smoother.enabled {
-                    transform.translation = smoother
-                        .last_value
-                        .lerp(target_pos, smoother.smoothness * time.delta_seconds());
-                    smoother.last_value = transform.translation;
-                } else {
-                    transform.translation = target_pos;
-                }
+    let delta = time.delta_seconds();
+    for (mut transform, position_smoother, rotation_smoother, zoom_smoother, orthographic, blend) in
+        cameras.iter_mut()
+    {
+        let focus = match position_smoother {
+            Some(mut smoother) => smoother.update(director.focus + director.pan, delta),
+            None => director.focus + director.pan,
+        };
+        // The rotation is smoothed rather than the position, so the camera swings around the target.
+        let rotation = match rotation_smoother {
+            Some(mut smoother) => smoother.update(orbit.rotation(), delta),
+            None => orbit.rotation(),
+        };
+
+        let zoom = match zoom_smoother {
+            Some(mut smoother) => smoother.update(director.zoom, delta),
+            None => director.zoom,
+        };
+
+        // Orthographic views don't get smaller as the camera moves back, so they're scaled instead.
+        if let Some(mut projection) = orthographic {
+            let scale = ORTHOGRAPHIC_SCALE * zoom;
+            if projection.scale != scale {
+                projection.scale = scale;
+            }
+        }
+
+        let target = Transform::from_translation(focus + rotation * CAMERA_OFFSET * zoom)
+            .looking_at(focus, Vec3::Y);
+        let blended = match blend {
+            Some(mut blend) => blend.update(director.mode, &transform, target, delta),
+            None => target,
+        };
+
+        // The free camera is moved by fly_camera instead.
+        if director.mode != CameraMode::Free {
+            *transform = blended;
+        }
+    }
+}
+
+/// Flies the camera around in the free mode.
+fn fly_camera(
+    director: Res<CameraDirector>,
+    actions: Res<ActionState>,
+    mut ev_motion: EventReader<MouseMotion>,
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+    time: Res<Time>,
+) {
+    let look = ev_motion
+        .iter()
+        .fold(Vec2::ZERO, |look, motion| look + motion.delta);
+    if director.mode != CameraMode::Free {
+        return;
+    }
+
+    for mut transform in cameras.iter_mut() {
+        if actions.pressed(Action::Look) {
+            let yaw = Quat::from_rotation_y(-look.x * FREE_LOOK_SPEED);
+            let pitch = Quat::from_rotation_x(-look.y * FREE_LOOK_SPEED);
+            transform.rotation = yaw * transform.rotation * pitch;
+        }
+
+        let mut movement = Vec3::ZERO;
+        let directions = [
+            (Action::FlyForward, transform.forward()),
+            (Action::FlyBack, transform.back()),
+            (Action::FlyLeft, transform.left()),
+            (Action::FlyRight, transform.right()),
+            (Action::FlyUp, Vec3::Y),
+            (Action::FlyDown, -Vec3::Y),
+        ];
+        for (action, direction) in directions {
+            if actions.pressed(action) {
+                movement += direction;
             }
-            None => transform.translation = target_pos,
         }
+        transform.translation += movement.normalize_or_zero() * FREE_SPEED * time.delta_seconds();
     }
 }
 
 /// Gets the cursor's position in normalised device coordinates (-1, 1)
 pub fn cursor_ndc(window: &Window) -> Option<Vec2> {
-    let cursor = window.cursor_position()?;
+    Some(screen_ndc(window, window.cursor_position()?))
+}
+
+/// Gets a position on the window, like a touch, in normalised device coordinates (-1, 1)
+pub fn screen_ndc(window: &Window, position: Vec2) -> Vec2 {
     let size = Vec2::new(window.width(), window.height());
-    Some(cursor / size * 2.0 - Vec2::ONE)
+    position / size * 2.0 - Vec2::ONE
 }
 
-/// The ray
+/// A line going out from a point in one direction, like from the camera through the cursor.
+#[derive(Debug, Clone, Copy)]
 pub struct Ray {
     pub origin: Vec3,
+    /// Always normalised.
     pub direction: Vec3,
 }
 
-/// Find the intersection point of this ray and an infinite plane.
 impl Ray {
-    /// Finds where this plane intersects on a plane.
-    pub fn intersect_plane(&self, normal: Vec3, origin: Vec3) -> Vec3 {
-        // TODO: Check for impossible conditions.
-        let origin = origin - self.origin;
-        let distance = origin.dot(normal) / self.direction.dot(normal);
+    /// Finds where this ray hits an infinite plane.
+    /// Returns none when the ray runs along the plane or points away from it.
+    pub fn intersect_plane(&self, normal: Vec3, origin: Vec3) -> Option<Vec3> {
+        let facing = self.direction.dot(normal);
+        if facing.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let distance = (origin - self.origin).dot(normal) / facing;
+        if distance < 0.0 || !distance.is_finite() {
+            return None;
+        }
+        Some(self.origin + self.direction * distance)
+    }
+
+    /// Gets the point a distance along the ray.
+    pub fn point_at(&self, distance: f32) -> Vec3 {
         self.origin + self.direction * distance
     }
 
-    /// Creates a ray from a normalised device coordinate.
-    /// Mostly copied from the bevy_mod_raycast crate.
+    /// Creates a ray from a normalised device coordinate, for both perspective and orthographic cameras.
+    /// Based on the bevy_mod_raycast crate.
     /// ref: https://docs.rs/bevy_mod_raycast/0.5.0/src/bevy_mod_raycast/primitives.rs.html#180
     pub fn from_screenspace(
         cursor_ndc: Option<Vec2>,
         camera: &Camera,
         camera_transform: &GlobalTransform,
     ) -> Option<Self> {
-        let view = camera_transform.compute_matrix();
-
         // Return none if there's no cursor.
-        cursor_ndc?;
-        let cursor_ndc = cursor_ndc.unwrap();
+        let cursor_ndc = cursor_ndc?;
 
         let projection = camera.projection_matrix;
-
-        // 2D Normalized device coordinate cursor position from (-1, -1) to (1, 1)
-        // let cursor_ndc = (cursor_ndc / screen_size) * 2.0 - Vec2::from([1.0, 1.0]);
-        let ndc_to_world: Mat4 = view * projection.inverse();
-        let world_to_ndc = projection * view;
+        let ndc_to_world: Mat4 = camera_transform.compute_matrix() * projection.inverse();
         let is_orthographic = projection.w_axis[3] == 1.0;
 
-        // Calculate the camera's near plane using the projection matrix
-        let projection = projection.to_cols_array_2d();
-        let camera_near = (2.0 * projection[3][2]) / (2.0 * projection[2][2] - 2.0);
-
-        // Compute the cursor position at the near plane. The bevy camera looks at -Z.
-        let ndc_near = world_to_ndc.transform_point3(-Vec3::Z * camera_near).z;
-        let cursor_pos_near = ndc_to_world.transform_point3(cursor_ndc.extend(ndc_near));
+        // Bevy's projections use reversed depth, so the near plane is at a depth of 1 in both of them.
+        let cursor_pos_near = ndc_to_world.project_point3(cursor_ndc.extend(1.0));
 
         // Compute the ray's direction depending on the projection used.
         let ray_direction = match is_orthographic {
-            true => view.transform_vector3(-Vec3::Z), // All screenspace rays are parallel in ortho
+            true => camera_transform.forward(), // All screenspace rays are parallel in ortho
             false => cursor_pos_near - camera_transform.translation, // Direction from camera to cursor
         };
 
+        // Cameras with a broken projection (like a window with no size) can't make a ray.
+        let direction = ray_direction.try_normalize()?;
+        if !cursor_pos_near.is_finite() {
+            return None;
+        }
+
         Some(Ray {
             origin: cursor_pos_near,
-            direction: ray_direction,
+            direction,
         })
     }
 }
@@ -0,0 +1,307 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+use bevy::{
+    asset::{AssetPlugin, AssetServerSettings, FileAssetIo},
+    ecs::event::{Events, ManualEventReader},
+    hierarchy::HierarchyPlugin,
+    prelude::*,
+    transform::TransformPlugin,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::ball::{
+    tee_position, BallBundle, Player, ShootEvent, ShotPlugin, KILL_HEIGHT, MIN_VELOCITY,
+};
+use crate::proc::{
+    course::{Course, Tee},
+    find_cup,
+    tile::Tile,
+    tile_definitions::TileDefinitions,
+    CoursePath, CurrentCourse, Ground, ProcPlugin, TileRegistry, Wall,
+};
+use crate::replay::{PhysicsClock, RecordPlugin};
+
+/// How long to wait for a course and it's tiles to load.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Shots are stopped after this many steps (a minute) even if the ball is still moving.
+const MAX_SHOT_STEPS: u64 = 60 * 60;
+/// How many steps in a row a ball has to be slow for before it's at rest.
+const REST_STEPS: u64 = 10;
+
+/// Plays a course without a window, rendering or audio.
+/// The physics takes the same fixed step as the game each update, so shots play out the same way.
+/// Shots are recorded like they are in the game, and a Playback can be inserted to check a replay.
+pub struct Simulation {
+    app: App,
+    collisions: ManualEventReader<CollisionEvent>,
+}
+
+/// Where a shot ended up.
+#[derive(Debug, Clone)]
+pub struct ShotResult {
+    /// Where the ball came to rest.
+    pub position: Vec3,
+    /// How many physics steps the shot took.
+    pub steps: u64,
+    /// Everything the ball hit, in order.
+    pub contacts: Vec<Contact>,
+    /// Whether the ball fell off the course.
+    pub out_of_bounds: bool,
+}
+
+/// The ball touching something during a shot.
+#[derive(Debug, Clone, Copy)]
+pub struct Contact {
+    /// The step of the shot the contact started on.
+    pub step: u64,
+    pub entity: Entity,
+    pub surface: Surface,
+    /// Where the ball was when the contact started.
+    pub position: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    Ground,
+    Wall,
+    Other,
+}
+
+/// Gets the file an asset path points to, the same way the asset server finds it.
+pub fn asset_file(path: &str) -> PathBuf {
+    FileAssetIo::get_root_path()
+        .join(AssetServerSettings::default().asset_folder)
+        .join(path)
+}
+
+impl Simulation {
+    /// Loads a course (relative to the assets folder) and waits for it's ground to be built.
+    pub fn load(course: &str) -> anyhow::Result<Simulation> {
+        let mut app = Simulation::app();
+        app.insert_resource(CoursePath(course.to_string()));
+        Simulation::start(app, course)
+    }
+
+    /// Plays a course that's already in memory, like one being edited, and waits for it's ground to be built.
+    pub fn from_course(course: Course) -> anyhow::Result<Simulation> {
+        let name = course.name.clone();
+        let mut app = Simulation::app();
+        let handle = app.world.resource_mut::<Assets<Course>>().add(course);
+        app.world.resource_mut::<CurrentCourse>().handle = handle;
+        Simulation::start(app, &name)
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .add_plugin(ProcPlugin)
+            .insert_resource(PhysicsClock::every_frame())
+            .add_plugin(ShotPlugin)
+            .add_plugin(RecordPlugin);
+        app
+    }
+
+    /// Waits for the course to load and it's hole to start.
+    fn start(app: App, course: &str) -> anyhow::Result<Simulation> {
+        let mut simulation = Simulation {
+            app,
+            collisions: ManualEventReader::default(),
+        };
+
+        // Assets load in the background, so keep updating until the hole has started.
+        let started = Instant::now();
+        while !simulation.app.world.resource::<CurrentCourse>().started() {
+            if started.elapsed() > LOAD_TIMEOUT {
+                bail!("Timed out loading course {}", course);
+            }
+            simulation.app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        Ok(simulation)
+    }
+
+    /// Gets the course's tee.
+    pub fn tee(&self) -> Tee {
+        self.app
+            .world
+            .resource::<CurrentCourse>()
+            .tee
+            .expect("The hole has started so the course has a tee")
+    }
+
+    /// Gets the course's tiles.
+    pub fn tiles(&mut self) -> Vec<Tile> {
+        let world = &mut self.app.world;
+        world.query::<&Tile>().iter(world).cloned().collect()
+    }
+
+    /// Gets the tile definitions of every tile pack.
+    pub fn registry(&self) -> &TileDefinitions {
+        self.app.world.resource::<TileRegistry>()
+    }
+
+    /// Gets where the cup is, if the course has one.
+    pub fn cup(&mut self) -> Option<Vec3> {
+        let tiles = self.tiles();
+        find_cup(&tiles, self.registry())
+    }
+
+    /// Steps the physics once, for when something other than a shot is playing out, like a replay.
+    pub fn step(&mut self) {
+        self.app.update();
+    }
+
+    /// Gets the world so tools can look at (or change) the course.
+    pub fn world(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    /// Puts a player's ball at a position and lets it settle, adding the ball if it doesn't exist yet.
+    pub fn place_ball(&mut self, player: usize, position: Vec3) -> Entity {
+        let existing = self
+            .app
+            .world
+            .query::<(Entity, &Player)>()
+            .iter(&self.app.world)
+            .find(|(_, ball_player)| ball_player.0 == player)
+            .map(|(ent, _)| ent);
+
+        let ball = match existing {
+            Some(ball) => {
+                let mut ball_ref = self.app.world.entity_mut(ball);
+                ball_ref.insert(Transform::from_translation(position));
+                ball_ref.insert(Velocity::default());
+                ball
+            }
+            None => self
+                .app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from_transform(
+                    Transform::from_translation(position),
+                ))
+                .insert_bundle(BallBundle::new(player))
+                .id(),
+        };
+
+        self.step_until_rest(ball);
+        ball
+    }
+
+    /// Puts a player's ball on the tee, the same way the game does at the start of a hole.
+    pub fn place_on_tee(&mut self, player: usize, players: usize) -> Entity {
+        let position = tee_position(&self.tee(), player, players);
+        self.place_ball(player, position)
+    }
+
+    /// Gets where a ball is.
+    pub fn ball_position(&self, ball: Entity) -> Option<Vec3> {
+        self.app
+            .world
+            .get::<Transform>(ball)
+            .map(|transform| transform.translation)
+    }
+
+    /// Shoots a ball and steps the physics until it comes to rest.
+    /// The aim is the same as a ShootEvent's, a direction scaled by power from 0 to 1.
+    pub fn shoot(&mut self, ball: Entity, aim: Vec3) -> ShotResult {
+        self.app
+            .world
+            .resource_mut::<Events<ShootEvent>>()
+            .send(ShootEvent { ball, aim });
+        self.step_until_rest(ball)
+    }
+
+    /// Updates the app until the ball stops moving, falls off the course or runs out of steps.
+    fn step_until_rest(&mut self, ball: Entity) -> ShotResult {
+        // Ignore anything that happened before now.
+        let events = self.app.world.resource::<Events<CollisionEvent>>();
+        self.collisions.iter(events).count();
+
+        let mut contacts = Vec::new();
+        let mut steps = 0;
+        let mut still_steps = 0;
+        let mut out_of_bounds = false;
+
+        while steps < MAX_SHOT_STEPS {
+            self.app.update();
+            steps += 1;
+
+            let position = self.ball_position(ball).unwrap_or_default();
+            self.collect_contacts(ball, steps, position, &mut contacts);
+
+            if position.y < KILL_HEIGHT {
+                out_of_bounds = true;
+                break;
+            }
+
+            let speed = self
+                .app
+                .world
+                .get::<Velocity>(ball)
+                .map(|velocity| velocity.linvel.length_squared())
+                .unwrap_or_default();
+            if speed < MIN_VELOCITY {
+                still_steps += 1;
+                if still_steps >= REST_STEPS {
+                    break;
+                }
+            } else {
+                still_steps = 0;
+            }
+        }
+
+        ShotResult {
+            position: self.ball_position(ball).unwrap_or_default(),
+            steps,
+            contacts,
+            out_of_bounds,
+        }
+    }
+
+    /// Adds the contacts the ball started this step.
+    fn collect_contacts(
+        &mut self,
+        ball: Entity,
+        step: u64,
+        position: Vec3,
+        contacts: &mut Vec<Contact>,
+    ) {
+        let world = &self.app.world;
+        let events = world.resource::<Events<CollisionEvent>>();
+
+        for event in self.collisions.iter(events) {
+            let other = match event {
+                CollisionEvent::Started(a, b, _) if *a == ball => *b,
+                CollisionEvent::Started(a, b, _) if *b == ball => *a,
+                _ => continue,
+            };
+
+            let surface = if world.get::<Ground>(other).is_some() {
+                Surface::Ground
+            } else if world.get::<Wall>(other).is_some() {
+                Surface::Wall
+            } else {
+                Surface::Other
+            };
+
+            contacts.push(Contact {
+                step,
+                entity: other,
+                surface,
+                position,
+            });
+        }
+    }
+}
@@ -0,0 +1,699 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use bevy::{
+    input::{
+        mouse::{MouseScrollUnit, MouseWheel},
+        InputSystem,
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+/// Where the bindings are saved after rebinding, and loaded from when the game starts.
+const BINDINGS_PATH: &str = "config/bindings.ron";
+/// Scrolling by pixels (on a touchpad) is turned into lines of this many pixels.
+const PIXELS_PER_LINE: f32 = 20.0;
+/// Sticks pushed less than this are left alone, so worn sticks don't drift.
+const STICK_DEADZONE: f32 = 0.3;
+/// How far a stick has to be pushed to be picked when rebinding.
+const REBIND_STICK_THRESHOLD: f32 = 0.5;
+/// The keys held for shortcuts like Ctrl+C.
+const CONTROL_KEYS: [KeyCode; 2] = [KeyCode::LControl, KeyCode::RControl];
+
+/// Turns the keyboard, mouse and gamepads into the Actions the player takes.
+/// F1 walks through every action asking for a new binding.
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>();
+        app.init_resource::<ActionState>();
+        app.init_resource::<Rebinding>();
+        app.add_startup_system(load_bindings);
+        app.add_system_to_stage(CoreStage::PreUpdate, update_actions.after(InputSystem));
+        app.add_system_to_stage(CoreStage::PreUpdate, rebind.after(update_actions));
+    }
+}
+
+/// Something the player can do, bound to any number of keys, buttons and sticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    /// Points the aim somewhere on the screen, from a gamepad stick.
+    Aim,
+    AimLeft,
+    AimRight,
+    /// Held to charge a shot, letting go takes it.
+    Charge,
+    /// Takes the shot straight away while charging.
+    Shoot,
+    /// Stops charging without taking the shot.
+    Cancel,
+    /// Puts the balls back on the tee.
+    Reset,
+    /// Switches between aiming on a flat plane and aiming at the course.
+    SwitchAimMode,
+    /// Switches the keyboard and gamepad power meter between filling up and oscillating.
+    ToggleMeter,
+    RotateCameraLeft,
+    RotateCameraRight,
+    ZoomIn,
+    ZoomOut,
+    /// Switches the camera between perspective and orthographic.
+    ToggleProjection,
+    FollowCamera,
+    OverviewCamera,
+    FlyoverCamera,
+    FreeCamera,
+    SkipFlyover,
+    FlyForward,
+    FlyBack,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    /// Held to look around with the mouse in the free camera.
+    Look,
+    /// Saves the shots taken on the hole as a replay.
+    SaveReplay,
+    /// Exports the course's ground and walls so they can be decorated in other tools.
+    ExportCourse,
+    CopyCourseCode,
+    /// Plays the course code on the clipboard.
+    PasteCourseCode,
+    /// Turns the terrain brush on and off.
+    ToggleBrush,
+    BrushSmaller,
+    BrushBigger,
+    /// Raises the cells under the terrain brush, or adds them while BrushAddOrRemove is held.
+    BrushRaise,
+    /// Lowers the cells under the terrain brush, or removes them while BrushAddOrRemove is held.
+    BrushLower,
+    BrushAddOrRemove,
+    /// Saves the course being painted over it's file.
+    SaveCourse,
+}
+
+impl Action {
+    /// Every action, in the order they're rebound.
+    pub const ALL: [Action; 37] = [
+        Action::Aim,
+        Action::AimLeft,
+        Action::AimRight,
+        Action::Charge,
+        Action::Shoot,
+        Action::Cancel,
+        Action::Reset,
+        Action::SwitchAimMode,
+        Action::ToggleMeter,
+        Action::RotateCameraLeft,
+        Action::RotateCameraRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ToggleProjection,
+        Action::FollowCamera,
+        Action::OverviewCamera,
+        Action::FlyoverCamera,
+        Action::FreeCamera,
+        Action::SkipFlyover,
+        Action::FlyForward,
+        Action::FlyBack,
+        Action::FlyLeft,
+        Action::FlyRight,
+        Action::FlyUp,
+        Action::FlyDown,
+        Action::Look,
+        Action::SaveReplay,
+        Action::ExportCourse,
+        Action::CopyCourseCode,
+        Action::PasteCourseCode,
+        Action::ToggleBrush,
+        Action::BrushSmaller,
+        Action::BrushBigger,
+        Action::BrushRaise,
+        Action::BrushLower,
+        Action::BrushAddOrRemove,
+        Action::SaveCourse,
+    ];
+
+    /// Whether the action points somewhere with a stick instead of being pressed.
+    pub fn is_stick(&self) -> bool {
+        matches!(self, Action::Aim)
+    }
+}
+
+/// An input an action can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A key pressed while Control is held, for shortcuts like Ctrl+C.
+    Ctrl(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any gamepad.
+    Button(GamepadButtonType),
+    /// Scrolling the mouse wheel one way.
+    Wheel(ScrollDirection),
+    /// A stick on any gamepad.
+    Stick(GamepadStick),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+impl GamepadStick {
+    fn axes(&self) -> (GamepadAxisType, GamepadAxisType) {
+        match self {
+            GamepadStick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            GamepadStick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY),
+        }
+    }
+}
+
+/// The kind of device a binding is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Keyboard,
+    Mouse,
+    Gamepad,
+}
+
+impl Binding {
+    pub fn device(&self) -> Device {
+        match self {
+            Binding::Key(_) | Binding::Ctrl(_) => Device::Keyboard,
+            Binding::Mouse(_) | Binding::Wheel(_) => Device::Mouse,
+            Binding::Button(_) | Binding::Stick(_) => Device::Gamepad,
+        }
+    }
+
+    fn pressed(&self, devices: &Devices) -> bool {
+        match self {
+            Binding::Key(key) => devices.plain(*key) && devices.keys.pressed(*key),
+            Binding::Ctrl(key) => devices.ctrl && devices.keys.pressed(*key),
+            Binding::Mouse(button) => devices.mouse.pressed(*button),
+            Binding::Button(button) => devices
+                .gamepad_buttons(*button)
+                .any(|button| devices.pad_buttons.pressed(button)),
+            Binding::Wheel(_) | Binding::Stick(_) => false,
+        }
+    }
+
+    fn just_pressed(&self, devices: &Devices) -> bool {
+        match self {
+            Binding::Key(key) => devices.plain(*key) && devices.keys.just_pressed(*key),
+            Binding::Ctrl(key) => devices.ctrl && devices.keys.just_pressed(*key),
+            Binding::Mouse(button) => devices.mouse.just_pressed(*button),
+            Binding::Button(button) => devices
+                .gamepad_buttons(*button)
+                .any(|button| devices.pad_buttons.just_pressed(button)),
+            Binding::Wheel(_) | Binding::Stick(_) => false,
+        }
+    }
+
+    fn just_released(&self, devices: &Devices) -> bool {
+        match self {
+            // Keys are let go whether or not Control is held, so nothing is left pressed.
+            Binding::Key(key) | Binding::Ctrl(key) => devices.keys.just_released(*key),
+            Binding::Mouse(button) => devices.mouse.just_released(*button),
+            Binding::Button(button) => devices
+                .gamepad_buttons(*button)
+                .any(|button| devices.pad_buttons.just_released(button)),
+            Binding::Wheel(_) | Binding::Stick(_) => false,
+        }
+    }
+
+    /// Gets how many lines the wheel was scrolled the binding's way.
+    fn scrolled(&self, devices: &Devices) -> f32 {
+        match self {
+            Binding::Wheel(ScrollDirection::Up) => devices.scroll.max(0.0),
+            Binding::Wheel(ScrollDirection::Down) => (-devices.scroll).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Gets where the binding's stick is pointing on the first gamepad that's pushing it.
+    fn stick(&self, devices: &Devices) -> Vec2 {
+        match self {
+            Binding::Stick(stick) => devices
+                .gamepads
+                .iter()
+                .map(|gamepad| devices.stick(*gamepad, *stick))
+                .find(|direction| direction.length() > STICK_DEADZONE)
+                .unwrap_or(Vec2::ZERO),
+            _ => Vec2::ZERO,
+        }
+    }
+}
+
+/// The inputs of every device for a frame.
+struct Devices<'a> {
+    keys: &'a Input<KeyCode>,
+    /// Whether Control is held, for shortcuts.
+    ctrl: bool,
+    mouse: &'a Input<MouseButton>,
+    gamepads: &'a Gamepads,
+    pad_buttons: &'a Input<GamepadButton>,
+    axes: &'a Axis<GamepadAxis>,
+    /// How many lines the mouse wheel scrolled up.
+    scroll: f32,
+}
+
+impl<'a> Devices<'a> {
+    /// Whether a key counts on it's own. Keys held with Control are shortcuts,
+    /// so Ctrl+S saves without also taking the action bound to S.
+    fn plain(&self, key: KeyCode) -> bool {
+        !self.ctrl || CONTROL_KEYS.contains(&key)
+    }
+
+    /// Gets a button on every gamepad.
+    fn gamepad_buttons(
+        &self,
+        button: GamepadButtonType,
+    ) -> impl Iterator<Item = GamepadButton> + 'a {
+        self.gamepads
+            .iter()
+            .map(move |gamepad| GamepadButton(*gamepad, button))
+    }
+
+    fn stick(&self, gamepad: Gamepad, stick: GamepadStick) -> Vec2 {
+        let (x, y) = stick.axes();
+        let axis = |axis_type| {
+            self.axes
+                .get(GamepadAxis(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        Vec2::new(axis(x), axis(y))
+    }
+}
+
+/// The inputs bound to each action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bindings(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::*;
+        use GamepadButtonType as Pad;
+
+        let bindings = [
+            (Action::Aim, vec![Stick(GamepadStick::Left)]),
+            (
+                Action::AimLeft,
+                vec![Key(KeyCode::Left), Button(Pad::DPadLeft)],
+            ),
+            (
+                Action::AimRight,
+                vec![Key(KeyCode::Right), Button(Pad::DPadRight)],
+            ),
+            (
+                Action::Charge,
+                vec![
+                    Mouse(MouseButton::Left),
+                    Key(KeyCode::Space),
+                    Button(Pad::South),
+                    Button(Pad::RightTrigger2),
+                ],
+            ),
+            (Action::Shoot, vec![Key(KeyCode::Return), Button(Pad::West)]),
+            (
+                Action::Cancel,
+                vec![
+                    Key(KeyCode::Escape),
+                    Mouse(MouseButton::Right),
+                    Button(Pad::East),
+                ],
+            ),
+            (Action::Reset, vec![Key(KeyCode::R), Button(Pad::Select)]),
+            (Action::SwitchAimMode, vec![Key(KeyCode::Tab)]),
+            (
+                Action::ToggleMeter,
+                vec![Key(KeyCode::M), Button(Pad::North)],
+            ),
+            (
+                Action::RotateCameraLeft,
+                vec![Key(KeyCode::Q), Button(Pad::LeftTrigger)],
+            ),
+            (
+                Action::RotateCameraRight,
+                vec![Key(KeyCode::E), Button(Pad::RightTrigger)],
+            ),
+            (
+                Action::ZoomIn,
+                vec![
+                    Wheel(ScrollDirection::Up),
+                    Key(KeyCode::Equals),
+                    Button(Pad::DPadUp),
+                ],
+            ),
+            (
+                Action::ZoomOut,
+                vec![
+                    Wheel(ScrollDirection::Down),
+                    Key(KeyCode::Minus),
+                    Button(Pad::DPadDown),
+                ],
+            ),
+            (Action::ToggleProjection, vec![Key(KeyCode::P)]),
+            (Action::FollowCamera, vec![Key(KeyCode::Key1)]),
+            (Action::OverviewCamera, vec![Key(KeyCode::Key2)]),
+            (Action::FlyoverCamera, vec![Key(KeyCode::Key3)]),
+            (Action::FreeCamera, vec![Key(KeyCode::Key4)]),
+            // These are the same as Charge, the ball ignores the press that skips the flyover.
+            (
+                Action::SkipFlyover,
+                vec![
+                    Key(KeyCode::Space),
+                    Mouse(MouseButton::Left),
+                    Button(Pad::South),
+                ],
+            ),
+            (Action::FlyForward, vec![Key(KeyCode::W)]),
+            (Action::FlyBack, vec![Key(KeyCode::S)]),
+            (Action::FlyLeft, vec![Key(KeyCode::A)]),
+            (Action::FlyRight, vec![Key(KeyCode::D)]),
+            // Q and E rotate the camera everywhere but the free camera, where they fly instead.
+            (Action::FlyUp, vec![Key(KeyCode::E)]),
+            (Action::FlyDown, vec![Key(KeyCode::Q)]),
+            (Action::Look, vec![Mouse(MouseButton::Right)]),
+            (Action::SaveReplay, vec![Key(KeyCode::F5)]),
+            (Action::ExportCourse, vec![Key(KeyCode::F9)]),
+            (Action::CopyCourseCode, vec![Ctrl(KeyCode::C)]),
+            (Action::PasteCourseCode, vec![Ctrl(KeyCode::V)]),
+            (Action::ToggleBrush, vec![Key(KeyCode::F2)]),
+            (Action::BrushSmaller, vec![Key(KeyCode::LBracket)]),
+            (Action::BrushBigger, vec![Key(KeyCode::RBracket)]),
+            // The ball ignores these while the brush is on.
+            (Action::BrushRaise, vec![Mouse(MouseButton::Left)]),
+            (Action::BrushLower, vec![Mouse(MouseButton::Right)]),
+            (
+                Action::BrushAddOrRemove,
+                vec![Key(KeyCode::LShift), Key(KeyCode::RShift)],
+            ),
+            (Action::SaveCourse, vec![Ctrl(KeyCode::S)]),
+        ];
+        Bindings(bindings.into_iter().collect())
+    }
+}
+
+impl Bindings {
+    /// Reads bindings from a file. Actions the file doesn't mention keep their default bindings.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Bindings> {
+        let text = fs::read_to_string(path)?;
+        let loaded: BTreeMap<Action, Vec<Binding>> = ron::de::from_str(&text)?;
+        let mut bindings = Bindings::default();
+        bindings.0.extend(loaded);
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        if let Some(folder) = path.as_ref().parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let text = ron::ser::to_string_pretty(&self.0, ron::ser::PrettyConfig::default())?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Gets the inputs bound to an action.
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0
+            .get(&action)
+            .map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    /// Binds an action to an input, replacing whatever it was bound to on the same device.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|existing| existing.device() != binding.device());
+        bindings.push(binding);
+    }
+}
+
+/// Which actions are being taken this frame.
+#[derive(Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+    scrolled: HashMap<Action, f32>,
+    sticks: HashMap<Action, Vec2>,
+    /// The device of the input that pressed or released each action.
+    devices: HashMap<Action, Device>,
+    /// Nothing happens after rebinding until every bound input has been let go,
+    /// so the inputs that were just bound don't do anything.
+    suppressed: bool,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether the action was let go this frame, and none of it's other inputs are still held.
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// Gets how many lines the mouse wheel was scrolled for an action this frame.
+    pub fn scrolled(&self, action: Action) -> f32 {
+        self.scrolled.get(&action).copied().unwrap_or(0.0)
+    }
+
+    /// Gets where a stick action is pointing, it's zero when the stick isn't being pushed.
+    pub fn stick(&self, action: Action) -> Vec2 {
+        self.sticks.get(&action).copied().unwrap_or(Vec2::ZERO)
+    }
+
+    /// Gets the device that pressed or released an action this frame, or is holding it.
+    pub fn device(&self, action: Action) -> Option<Device> {
+        self.devices.get(&action).copied()
+    }
+}
+
+/// Asks for a new binding for each action in turn.
+#[derive(Default)]
+pub struct Rebinding {
+    /// The index in Action::ALL of the action waiting for a binding.
+    next: Option<usize>,
+}
+
+impl Rebinding {
+    pub fn active(&self) -> bool {
+        self.next.is_some()
+    }
+}
+
+fn load_bindings(mut bindings: ResMut<Bindings>) {
+    if !Path::new(BINDINGS_PATH).exists() {
+        return;
+    }
+    match Bindings::load(BINDINGS_PATH) {
+        Ok(loaded) => *bindings = loaded,
+        Err(err) => error!("Failed to load the bindings, using the defaults: {}", err),
+    }
+}
+
+/// Gets how many lines the mouse wheel scrolled up this frame.
+fn scroll_lines(ev_scroll: &mut EventReader<MouseWheel>) -> f32 {
+    ev_scroll
+        .iter()
+        .map(|scroll| match scroll.unit {
+            MouseScrollUnit::Line => scroll.y,
+            MouseScrollUnit::Pixel => scroll.y / PIXELS_PER_LINE,
+        })
+        .sum()
+}
+
+fn update_actions(
+    mut state: ResMut<ActionState>,
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    pad_buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut ev_scroll: EventReader<MouseWheel>,
+) {
+    let devices = Devices {
+        keys: &keys,
+        ctrl: keys.any_pressed(CONTROL_KEYS),
+        mouse: &mouse,
+        gamepads: &gamepads,
+        pad_buttons: &pad_buttons,
+        axes: &axes,
+        scroll: scroll_lines(&mut ev_scroll),
+    };
+
+    let suppressed = rebinding.active()
+        || (state.suppressed
+            && bindings
+                .0
+                .values()
+                .flatten()
+                .any(|binding| binding.pressed(&devices)));
+    *state = ActionState {
+        suppressed,
+        ..default()
+    };
+    if suppressed {
+        return;
+    }
+
+    for (action, action_bindings) in bindings.0.iter() {
+        let action = *action;
+        for binding in action_bindings {
+            if binding.pressed(&devices) {
+                state.pressed.insert(action);
+                state.devices.insert(action, binding.device());
+            }
+            if binding.just_pressed(&devices) {
+                state.just_pressed.insert(action);
+                state.devices.insert(action, binding.device());
+            }
+            if binding.just_released(&devices) && !state.devices.contains_key(&action) {
+                state.just_released.insert(action);
+                state.devices.insert(action, binding.device());
+            }
+
+            let scrolled = binding.scrolled(&devices);
+            if scrolled > 0.0 {
+                *state.scrolled.entry(action).or_default() += scrolled;
+                state.devices.insert(action, binding.device());
+            }
+            let stick = binding.stick(&devices);
+            if stick != Vec2::ZERO && !state.sticks.contains_key(&action) {
+                state.sticks.insert(action, stick);
+                state.devices.insert(action, binding.device());
+            }
+        }
+
+        // Actions are only released once all of their inputs are let go.
+        if state.pressed.contains(&action) {
+            state.just_released.remove(&action);
+        }
+    }
+}
+
+/// Walks through every action when F1 is pressed, binding each to the next input that's pressed.
+/// Escape keeps an action's bindings, and the new bindings are saved once every action has been through.
+fn rebind(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    pad_buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut ev_scroll: EventReader<MouseWheel>,
+) {
+    let scroll = scroll_lines(&mut ev_scroll);
+    let index = match rebinding.next {
+        Some(index) => index,
+        None => {
+            if keys.just_pressed(KeyCode::F1) {
+                rebinding.next = Some(0);
+                prompt(Action::ALL[0], &bindings);
+            }
+            return;
+        }
+    };
+    let action = Action::ALL[index];
+
+    if !keys.just_pressed(KeyCode::Escape) {
+        let devices = Devices {
+            keys: &keys,
+            ctrl: keys.any_pressed(CONTROL_KEYS),
+            mouse: &mouse,
+            gamepads: &gamepads,
+            pad_buttons: &pad_buttons,
+            axes: &axes,
+            scroll,
+        };
+        let binding = match action.is_stick() {
+            true => pushed_stick(&devices),
+            false => pressed_binding(&devices),
+        };
+        match binding {
+            Some(binding) => {
+                bindings.rebind(action, binding);
+                info!("Bound {:?} to {:?}", action, binding);
+            }
+            None => return,
+        }
+    }
+
+    match Action::ALL.get(index + 1) {
+        Some(next) => {
+            rebinding.next = Some(index + 1);
+            prompt(*next, &bindings);
+        }
+        None => {
+            rebinding.next = None;
+            match bindings.save(BINDINGS_PATH) {
+                Ok(()) => info!("Saved the bindings to {}", BINDINGS_PATH),
+                Err(err) => error!("Failed to save the bindings: {}", err),
+            }
+        }
+    }
+}
+
+fn prompt(action: Action, bindings: &Bindings) {
+    info!(
+        "Press a new binding for {:?} (currently {:?}) or Escape to keep it",
+        action,
+        bindings.get(action)
+    );
+}
+
+/// Gets the first input that was pressed this frame, apart from the ones rebinding uses.
+/// Control is kept for shortcuts, so a key pressed while it's held is bound with it.
+fn pressed_binding(devices: &Devices) -> Option<Binding> {
+    if let Some(key) = devices
+        .keys
+        .get_just_pressed()
+        .find(|key| !matches!(key, KeyCode::F1 | KeyCode::Escape) && !CONTROL_KEYS.contains(key))
+    {
+        return Some(match devices.ctrl {
+            true => Binding::Ctrl(*key),
+            false => Binding::Key(*key),
+        });
+    }
+    if let Some(button) = devices.mouse.get_just_pressed().next() {
+        return Some(Binding::Mouse(*button));
+    }
+    if devices.scroll != 0.0 {
+        return Some(Binding::Wheel(match devices.scroll > 0.0 {
+            true => ScrollDirection::Up,
+            false => ScrollDirection::Down,
+        }));
+    }
+    devices
+        .pad_buttons
+        .get_just_pressed()
+        .next()
+        .map(|button| Binding::Button(button.1))
+}
+
+/// Gets the first gamepad stick that's being pushed most of the way.
+fn pushed_stick(devices: &Devices) -> Option<Binding> {
+    devices.gamepads.iter().find_map(|gamepad| {
+        [GamepadStick::Left, GamepadStick::Right]
+            .into_iter()
+            .find(|stick| devices.stick(*gamepad, *stick).length() > REBIND_STICK_THRESHOLD)
+            .map(Binding::Stick)
+    })
+}
@@ -0,0 +1,9 @@
+pub mod ball;
+pub mod bot;
+pub mod camera;
+pub mod headless;
+pub mod input;
+pub mod par;
+pub mod proc;
+pub mod replay;
+pub mod validate;
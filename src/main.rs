@@ -1,25 +1,68 @@
 use bevy::{asset::AssetServerSettings, prelude::*};
 use bevy_prototype_debug_lines::*;
 use bevy_rapier3d::{prelude::*, rapier::prelude::HalfSpace, plugin::systems::ColliderComponents};
-mod ball;
-mod camera;
-mod proc;
+use bevy_golf::{ball, bot, camera, input, proc, replay};
 
 fn main() {
-    App::new()
-        .insert_resource(AssetServerSettings {
-            watch_for_changes: true,
-            ..default()
-        })
-        .add_plugins(DefaultPlugins)
-        .add_plugin(DebugLinesPlugin::default())
-        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
-        // .add_plugin(RapierDebugRenderPlugin::default())
-        .add_plugin(proc::ProcPlugin)
-        .add_plugin(ball::BallPlugin)
-        .add_plugin(camera::CameraPlugin)
-        .add_startup_system(setup_scene)
-        .run();
+    let mut app = App::new();
+
+    // Play back a replay with `--replay <file>`.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = arg_value(&args, "--replay") {
+        match replay::Replay::load(path) {
+            Ok(replay) => {
+                app.insert_resource(proc::CoursePath(replay.course.clone()));
+                app.insert_resource(ball::Players(replay.players));
+                app.insert_resource(replay::Playback::new(replay));
+            }
+            Err(err) => eprintln!("Failed to load replay {}: {}", path, err),
+        }
+    }
+
+    // Add bots after the player with `--bots <count>` and `--difficulty <easy|normal|hard>`.
+    let bot_count = arg_value(&args, "--bots")
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or(0);
+    if bot_count > 0 {
+        let difficulty = arg_value(&args, "--difficulty")
+            .and_then(|name| bot::Difficulty::from_name(name))
+            .unwrap_or(bot::Difficulty::Normal);
+        let humans = 1;
+        app.insert_resource(ball::Players(humans + bot_count));
+        app.insert_resource(bot::Bots(
+            (humans..humans + bot_count)
+                .map(|player| (player, difficulty))
+                .collect(),
+        ));
+    }
+
+    app.insert_resource(AssetServerSettings {
+        watch_for_changes: true,
+        ..default()
+    })
+    .add_plugins(DefaultPlugins)
+    .add_plugin(DebugLinesPlugin::default())
+    .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+    // .add_plugin(RapierDebugRenderPlugin::default())
+    .add_plugin(input::ActionPlugin)
+    .add_plugin(proc::ProcPlugin)
+    .add_plugin(proc::export::ExportPlugin)
+    .add_plugin(proc::course_code::CourseCodePlugin)
+    .add_plugin(proc::terrain_brush::TerrainBrushPlugin)
+    .add_plugin(proc::wall_cutaway::WallCutawayPlugin)
+    .add_plugin(ball::BallPlugin)
+    .add_plugin(camera::CameraPlugin)
+    .add_plugin(replay::ReplayPlugin)
+    .add_plugin(bot::BotPlugin)
+    .add_startup_system(setup_scene)
+    .run();
+}
+
+/// Gets the value that follows a command line flag.
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
 }
 
 fn setup_scene(mut commands: Commands) {
@@ -0,0 +1,122 @@
+use anyhow::anyhow;
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::bot::{plan_shot, Difficulty};
+use crate::headless::Simulation;
+use crate::proc::{
+    course::Tee, in_cup, tile::Tile, tile_definitions::TileDefinitions, tile_graph::TileGraph,
+};
+
+/// How far (in tiles) a good shot travels along the route.
+const TILES_PER_SHOT: f32 = 5.0;
+/// How many times the hole is played to count the shots it takes.
+const TRIALS: usize = 3;
+/// Holes that take more shots than this are given up on.
+const MAX_SHOTS: u32 = 12;
+/// Shots that leave the course cost an extra shot.
+const OUT_OF_BOUNDS_PENALTY: u32 = 1;
+/// The simulated players aim like good players, not perfect ones.
+const PLAYER_DIFFICULTY: Difficulty = Difficulty::Hard;
+
+/// The tiles a ball rolls over to get from the tee to the cup.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub tiles: Vec<IVec3>,
+    /// The distance between the centres of the tiles along the route.
+    pub length: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParEstimate {
+    pub route: Route,
+    /// The par from the length of the route alone.
+    pub route_par: u32,
+    /// The average shots the simulated players took.
+    pub simulated_shots: f32,
+    /// The suggested par.
+    pub par: u32,
+}
+
+/// Finds the shortest route from the tee to the cup, over tiles that share an edge.
+pub fn shortest_route(tiles: &[Tile], tee: &Tee, defs: &TileDefinitions) -> anyhow::Result<Route> {
+    let start = tiles
+        .iter()
+        .position(|tile| tile.position == tee.position)
+        .ok_or_else(|| anyhow!("There's no tile under the tee at {}", tee.position))?;
+    let goal = tiles
+        .iter()
+        .position(|tile| defs.get(&tile.tile_type).map_or(false, |def| def.cup))
+        .ok_or_else(|| anyhow!("The course doesn't have a cup"))?;
+
+    let graph = TileGraph::new(tiles, defs);
+    let (path, length) = graph
+        .shortest_path(start, goal)
+        .ok_or_else(|| anyhow!("There's no route from the tee to the cup"))?;
+
+    Ok(Route {
+        tiles: path
+            .into_iter()
+            .map(|index| tiles[index].position)
+            .collect(),
+        length,
+    })
+}
+
+/// Plays the hole from the tee until the ball drops in the cup, returning how many shots it took.
+pub fn simulate_hole(simulation: &mut Simulation, rng: &mut StdRng) -> anyhow::Result<u32> {
+    let cup = simulation
+        .cup()
+        .ok_or_else(|| anyhow!("The course doesn't have a cup"))?;
+    let ball = simulation.place_on_tee(0, 1);
+    let mut position = simulation.ball_position(ball).unwrap_or_default();
+    let mut shots = 0;
+
+    while shots < MAX_SHOTS {
+        let aim = plan_shot(simulation, 0, position)?;
+
+        // Planning moves the ball around, so put it back before the real shot.
+        let ball = simulation.place_ball(0, position);
+        let result = simulation.shoot(ball, PLAYER_DIFFICULTY.add_noise(aim, rng));
+        shots += 1;
+
+        if result.out_of_bounds {
+            shots += OUT_OF_BOUNDS_PENALTY;
+            continue;
+        }
+        position = result.position;
+        if in_cup(position, cup) {
+            break;
+        }
+    }
+
+    Ok(shots)
+}
+
+/// Suggests a par for a course (relative to the assets folder).
+/// The route par is a shot for every TILES_PER_SHOT of the route plus a putt,
+/// it's averaged with how many shots simulated players took.
+pub fn estimate_par(course: &str) -> anyhow::Result<ParEstimate> {
+    let mut simulation = Simulation::load(course)?;
+    let tiles = simulation.tiles();
+    let route = shortest_route(&tiles, &simulation.tee(), simulation.registry())?;
+    let route_par = 1 + (route.length / TILES_PER_SHOT).ceil() as u32;
+
+    // Seeded so the same course always gets the same par.
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut total = 0;
+    for _ in 0..TRIALS {
+        total += simulate_hole(&mut simulation, &mut rng)?;
+    }
+    let simulated_shots = total as f32 / TRIALS as f32;
+
+    let par = ((route_par as f32 + simulated_shots) * 0.5)
+        .round()
+        .max(2.0) as u32;
+    Ok(ParEstimate {
+        route,
+        route_par,
+        simulated_shots,
+        par,
+    })
+}
@@ -0,0 +1,240 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    tile::{Orientation, Tile},
+    tile_definitions::TileDefinitions,
+};
+
+const ORIENTATIONS: [Orientation; 4] = [
+    Orientation::North,
+    Orientation::East,
+    Orientation::South,
+    Orientation::West,
+];
+
+/// Which of a cell's corners are raised, in the same order as the top of TILE_VERTS
+/// (+X-Z, +X+Z, -X+Z, -X-Z).
+pub type Corners = [bool; 4];
+
+/// Picks tile types and orientations from the heights of a grid of cells.
+/// Any tile definition with a square or triangle perimeter can be picked, so tile packs can add their own slopes.
+pub struct AutoTiler {
+    /// The tile type and orientation that makes each shape of raised corners.
+    shapes: HashMap<Corners, (String, Orientation)>,
+    /// The flat tile type and orientation that cuts off each corner, for the outside corners of a course.
+    cuts: HashMap<usize, (String, Orientation)>,
+}
+
+impl AutoTiler {
+    pub fn new(defs: &TileDefinitions) -> Self {
+        let mut shapes = HashMap::default();
+        let mut cuts = HashMap::default();
+
+        for def in defs.defs.iter() {
+            // Tiles with meshes, cups or hazards are placed on purpose, not picked by shape.
+            if def.mesh.is_some() || def.cup || def.hazard {
+                continue;
+            }
+
+            for rotation in ORIENTATIONS {
+                let tile = Tile {
+                    position: IVec3::ZERO,
+                    rotation,
+                    flip: Default::default(),
+                    tile_type: def.key.clone(),
+                };
+                // The first definition (and orientation) to make a shape wins.
+                let choice = (def.key.clone(), rotation);
+                match corners_of(&tile, &def.perimeter) {
+                    [Some(a), Some(b), Some(c), Some(d)] => {
+                        shapes.entry([a, b, c, d]).or_insert(choice);
+                    }
+                    corners
+                        if def.perimeter.len() == 3 && corners.iter().flatten().all(|top| *top) =>
+                    {
+                        let cut = corners.iter().position(|corner| corner.is_none()).unwrap();
+                        cuts.entry(cut).or_insert(choice);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        Self { shapes, cuts }
+    }
+
+    /// Gets the tile that fits a cell, or None when no definition has that shape.
+    pub fn tile(&self, position: IVec3, corners: Corners) -> Option<Tile> {
+        self.shapes.get(&corners).map(|(tile_type, rotation)| Tile {
+            position,
+            rotation: *rotation,
+            flip: Default::default(),
+            tile_type: tile_type.clone(),
+        })
+    }
+
+    /// Turns a grid of heights (in TILE_BOUNDS.y steps) into tiles.
+    /// Corners shared with higher cells are raised, so slopes are placed on the low side of each step.
+    /// Cells in `fixed` are given that tile type flat, at their own height.
+    pub fn tile_grid(
+        &self,
+        heights: &HashMap<IVec2, i32>,
+        fixed: &HashMap<IVec2, String>,
+    ) -> Vec<Tile> {
+        let mut cells: Vec<&IVec2> = heights.keys().collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+
+        let mut tiles = Vec::new();
+        for cell in cells {
+            let height = heights[cell];
+            let flat = IVec3::new(cell.x, height, cell.y);
+
+            if let Some(tile_type) = fixed.get(cell) {
+                tiles.push(Tile {
+                    position: flat,
+                    rotation: Orientation::North,
+                    flip: Default::default(),
+                    tile_type: tile_type.clone(),
+                });
+                continue;
+            }
+
+            match self.tile_cell(heights, *cell) {
+                Some(tile) => tiles.push(tile),
+                None => warn!("No tile definition fits the cell at {}", cell),
+            }
+        }
+
+        tiles
+    }
+
+    /// Picks the tile for one cell of a grid of heights, from the heights of the cells around it.
+    pub fn tile_cell(&self, heights: &HashMap<IVec2, i32>, cell: IVec2) -> Option<Tile> {
+        let flat = IVec3::new(cell.x, *heights.get(&cell)?, cell.y);
+
+        let corners = raised_corners(heights, cell);
+        let tile = match corners.iter().any(|raised| *raised) {
+            // The tile's top face is one step up, with it's lowered corners meeting this cell's height.
+            true => self.tile(flat + IVec3::Y, corners),
+            false => outside_corner(heights, cell)
+                .and_then(|cut| self.cuts.get(&cut))
+                .map(|(tile_type, rotation)| Tile {
+                    position: flat,
+                    rotation: *rotation,
+                    flip: Default::default(),
+                    tile_type: tile_type.clone(),
+                }),
+        };
+        tile.or_else(|| self.tile(flat, [true; 4]))
+    }
+}
+
+/// A course's tiles as a grid of heights, so the ground can be reshaped and tiled again.
+#[derive(Debug, Clone, Default)]
+pub struct HeightGrid {
+    /// The height of each cell in TILE_BOUNDS.y steps, cells without a height have no tile.
+    pub heights: HashMap<IVec2, i32>,
+    /// Cells with tiles the AutoTiler doesn't make (like cups, hazards and stacked tiles),
+    /// which should be kept as they are.
+    pub fixed: HashMap<IVec2, String>,
+}
+
+impl HeightGrid {
+    /// Works out the height of each cell from the tiles in it.
+    /// Slopes sit a step above the cell, with their lowered corners on the cell's height.
+    pub fn from_tiles<'a>(
+        tiles: impl IntoIterator<Item = &'a Tile>,
+        defs: &TileDefinitions,
+    ) -> Self {
+        let mut grid = HeightGrid::default();
+
+        for tile in tiles {
+            let cell = IVec2::new(tile.position.x, tile.position.z);
+            let shape = match defs.get(&tile.tile_type) {
+                Ok(def) if def.mesh.is_none() && !def.cup && !def.hazard && tile.flip.is_none() => {
+                    Some(corners_of(tile, &def.perimeter))
+                }
+                _ => None,
+            };
+
+            let (height, fixed) = match shape {
+                Some(corners) if corners.iter().flatten().all(|top| *top) => {
+                    (tile.position.y, false)
+                }
+                Some([Some(_), Some(_), Some(_), Some(_)]) => (tile.position.y - 1, false),
+                _ => (tile.position.y, true),
+            };
+
+            match grid.heights.get(&cell).copied() {
+                // More than one tile in a cell is kept as it is, at the height of the highest.
+                Some(other) => {
+                    grid.heights.insert(cell, other.max(height));
+                    grid.fixed.insert(cell, tile.tile_type.clone());
+                }
+                None => {
+                    grid.heights.insert(cell, height);
+                    if fixed {
+                        grid.fixed.insert(cell, tile.tile_type.clone());
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+}
+
+/// The direction of each corner from the middle of a cell.
+const CORNER_SIGNS: [(i32, i32); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
+
+/// Gets the cells that share a corner with a cell.
+fn corner_neighbours(cell: IVec2, corner: usize) -> [IVec2; 3] {
+    let (x, z) = CORNER_SIGNS[corner];
+    [
+        cell + IVec2::new(x, 0),
+        cell + IVec2::new(0, z),
+        cell + IVec2::new(x, z),
+    ]
+}
+
+/// Gets which corners of a cell touch a higher cell.
+pub fn raised_corners(heights: &HashMap<IVec2, i32>, cell: IVec2) -> Corners {
+    let height = heights[&cell];
+    [0, 1, 2, 3].map(|corner| {
+        corner_neighbours(cell, corner)
+            .iter()
+            .filter_map(|neighbour| heights.get(neighbour))
+            .any(|neighbour| *neighbour > height)
+    })
+}
+
+/// Gets the corner of a cell that sticks out of the course, when the cells on both sides of it
+/// are empty and the cells on the other two sides aren't.
+fn outside_corner(heights: &HashMap<IVec2, i32>, cell: IVec2) -> Option<usize> {
+    (0..4).find(|corner| {
+        let opposite = corner_neighbours(cell, (corner + 2) % 4);
+        corner_neighbours(cell, *corner)
+            .iter()
+            .all(|neighbour| !heights.contains_key(neighbour))
+            && opposite[..2]
+                .iter()
+                .all(|neighbour| heights.contains_key(neighbour))
+    })
+}
+
+/// Gets which corners of a tile are on it's top face, None for corners it's perimeter doesn't reach.
+fn corners_of(tile: &Tile, perimeter: &[u8]) -> [Option<bool>; 4] {
+    let mut corners = [None; 4];
+    for index in perimeter {
+        let point = tile.lattice_point(*index);
+        let corner = match (point.x > 0.0, point.z > 0.0) {
+            (true, false) => 0,
+            (true, true) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        };
+        corners[corner] = Some(point.y >= 0.0);
+    }
+
+    corners
+}
@@ -0,0 +1,84 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use bevy::{
+    asset::{AssetLoader, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+
+use super::{migrate::read_course, tile::*};
+
+/// A hole's layout, loaded from a .course.ron file.
+#[derive(Debug, Clone, Deserialize, Serialize, TypeUuid)]
+#[uuid = "5c1f3b8e-2d4a-4f6e-9a7b-0c8d1e2f3a4b"]
+pub struct Course {
+    /// The version of the file format, see the migrate module.
+    pub version: u32,
+    pub name: String,
+    /// How many shots the hole should take, see the bevy-golf-par tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub par: Option<u32>,
+    pub tee: Tee,
+    pub tiles: Vec<Tile>,
+}
+
+impl Course {
+    /// Reads a course straight from a file, for tools that run outside of the asset server.
+    /// Courses from older versions are upgraded.
+    pub fn load_file(path: impl AsRef<Path>) -> anyhow::Result<Course> {
+        Ok(read_course(&fs::read(path)?)?.value)
+    }
+
+    /// Writes the course to a file, with each tile on it's own line.
+    pub fn save_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let config = ron::ser::PrettyConfig::new().depth_limit(2);
+        let text = ron::ser::to_string_pretty(self, config)?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// Where the balls are placed at the start of a hole and when they're reset.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Tee {
+    /// The tile the balls are placed on.
+    pub position: IVec3,
+    /// The direction the players face when teeing off.
+    pub facing: Orientation,
+}
+
+impl Tee {
+    /// Gets the centre of the top of the tee's tile.
+    pub fn world_position(&self) -> Vec3 {
+        self.position.as_vec3() * TILE_BOUNDS
+    }
+
+    /// Gets the direction the players face.
+    pub fn forward(&self) -> Vec3 {
+        self.facing.rotation() * -Vec3::Z
+    }
+}
+
+#[derive(Default)]
+pub struct CourseLoader;
+
+impl AssetLoader for CourseLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let course = read_course(bytes)?.value;
+            load_context.set_default_asset(LoadedAsset::new(course));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["course.ron"]
+    }
+}
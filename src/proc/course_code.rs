@@ -0,0 +1,316 @@
+use anyhow::{anyhow, bail};
+use bevy::prelude::*;
+
+use super::{
+    course::{Course, Tee},
+    migrate::COURSE_VERSION,
+    tile::{Flip, Orientation, Tile},
+    CurrentCourse,
+};
+use crate::input::{Action, ActionState};
+
+/// The version of the encoding, the first byte of every code.
+pub const COURSE_CODE_VERSION: u8 = 1;
+
+/// Copies the course being played as a code with Ctrl+C, and plays the code on the clipboard with Ctrl+V.
+pub struct CourseCodePlugin;
+impl Plugin for CourseCodePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(copy_course_code);
+        app.add_system(paste_course_code);
+    }
+}
+
+/// Encodes a course as a short URL-safe string that can be pasted into chat.
+///
+/// The bytes are, in order: the version, the name, the par, the tee, a table of the tile types
+/// and then the tiles. Tiles are sorted and grouped into runs along X, each run is it's start
+/// position and length followed by the rotation, flip and type of each tile in it.
+/// The cup is whichever tile has the cup's type. Numbers are varints, signed ones are zigzagged.
+pub fn encode_course(course: &Course) -> String {
+    let mut writer = Writer::default();
+    writer.bytes.push(COURSE_CODE_VERSION);
+    writer.string(&course.name);
+    writer.varint(course.par.map_or(0, |par| par as u64 + 1));
+    writer.position(course.tee.position);
+    writer.bytes.push(course.tee.facing as u8);
+
+    // Tile types are written once, tiles refer to them by their index in the table.
+    let mut types: Vec<&str> = Vec::new();
+    for tile in course.tiles.iter() {
+        if !types.contains(&tile.tile_type.as_str()) {
+            types.push(&tile.tile_type);
+        }
+    }
+    writer.varint(types.len() as u64);
+    for tile_type in types.iter() {
+        writer.string(tile_type);
+    }
+
+    let mut tiles: Vec<&Tile> = course.tiles.iter().collect();
+    tiles.sort_by_key(|tile| tile_order(tile));
+
+    let mut runs: Vec<Vec<&Tile>> = Vec::new();
+    for tile in tiles {
+        match runs.last_mut() {
+            Some(run) if run[run.len() - 1].position + IVec3::X == tile.position => run.push(tile),
+            _ => runs.push(vec![tile]),
+        }
+    }
+
+    writer.varint(runs.len() as u64);
+    for run in runs {
+        writer.position(run[0].position);
+        writer.varint(run.len() as u64);
+        for tile in run {
+            let type_index = types.iter().position(|t| *t == tile.tile_type).unwrap();
+            // The rotation and flip take up the low 4 bits, the type the rest.
+            let packed = (type_index as u64) << 4
+                | (flip_index(tile.flip) as u64) << 2
+                | tile.rotation as u64;
+            writer.varint(packed);
+        }
+    }
+
+    base64::encode_config(&writer.bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Decodes a course from a code made by `encode_course`.
+/// The tiles come back sorted, so they may be in a different order to the course that was encoded.
+pub fn decode_course(code: &str) -> anyhow::Result<Course> {
+    let bytes = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD)
+        .map_err(|err| anyhow!("The course code isn't valid: {}", err))?;
+    let mut reader = Reader {
+        bytes: &bytes,
+        offset: 0,
+    };
+
+    let version = reader.byte()?;
+    if version != COURSE_CODE_VERSION {
+        bail!(
+            "The course code is version {} but only version {} is supported",
+            version,
+            COURSE_CODE_VERSION
+        );
+    }
+
+    let name = reader.string()?;
+    let par = match reader.varint()? {
+        0 => None,
+        par => {
+            Some(u32::try_from(par - 1).map_err(|_| anyhow!("The course code's par is too big"))?)
+        }
+    };
+    let tee = Tee {
+        position: reader.position()?,
+        facing: orientation(reader.byte()? as u64)?,
+    };
+
+    let type_count = reader.varint()?;
+    let mut types = Vec::new();
+    for _ in 0..type_count {
+        types.push(reader.string()?);
+    }
+
+    let mut tiles = Vec::new();
+    let run_count = reader.varint()?;
+    for _ in 0..run_count {
+        let start = reader.position()?;
+        let length = reader.varint()?;
+        for i in 0..length {
+            let packed = reader.varint()?;
+            let tile_type = types
+                .get((packed >> 4) as usize)
+                .ok_or_else(|| anyhow!("A tile in the course code has an unknown type"))?;
+            let x = i32::try_from(i)
+                .ok()
+                .and_then(|i| start.x.checked_add(i))
+                .ok_or_else(|| anyhow!("A run of tiles in the course code goes off the edge"))?;
+            tiles.push(Tile {
+                position: IVec3::new(x, start.y, start.z),
+                rotation: orientation(packed & 0b11)?,
+                flip: flip((packed >> 2) & 0b11)?,
+                tile_type: tile_type.clone(),
+            });
+        }
+    }
+
+    if reader.offset != bytes.len() {
+        bail!("The course code has extra bytes at the end");
+    }
+
+    Ok(Course {
+        version: COURSE_VERSION,
+        name,
+        par,
+        tee,
+        tiles,
+    })
+}
+
+/// The order tiles are encoded in, so tiles next to each other along X are in the same run.
+pub fn tile_order(tile: &Tile) -> (i32, i32, i32) {
+    (tile.position.y, tile.position.z, tile.position.x)
+}
+
+fn flip_index(flip: Flip) -> u8 {
+    match flip {
+        Flip::None => 0,
+        Flip::Mirror => 1,
+    }
+}
+
+fn flip(index: u64) -> anyhow::Result<Flip> {
+    match index {
+        0 => Ok(Flip::None),
+        1 => Ok(Flip::Mirror),
+        _ => bail!("A tile in the course code has an unknown flip"),
+    }
+}
+
+fn orientation(index: u64) -> anyhow::Result<Orientation> {
+    match index {
+        0 => Ok(Orientation::North),
+        1 => Ok(Orientation::East),
+        2 => Ok(Orientation::South),
+        3 => Ok(Orientation::West),
+        _ => bail!("The course code has an unknown orientation"),
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    /// Writes 7 bits at a time, with the top bit set when there's more to come.
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    /// Zigzags a signed number so small negative numbers stay small.
+    fn signed(&mut self, value: i32) {
+        self.varint(((value << 1) ^ (value >> 31)) as u32 as u64);
+    }
+
+    fn position(&mut self, position: IVec3) {
+        self.signed(position.x);
+        self.signed(position.y);
+        self.signed(position.z);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.varint(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        let byte = *self
+            .bytes
+            .get(self.offset)
+            .ok_or_else(|| anyhow!("The course code is cut short"))?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> anyhow::Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("The course code has a number that's too big")
+    }
+
+    fn signed(&mut self) -> anyhow::Result<i32> {
+        let value = u32::try_from(self.varint()?)?;
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+
+    fn position(&mut self) -> anyhow::Result<IVec3> {
+        Ok(IVec3::new(self.signed()?, self.signed()?, self.signed()?))
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let length = self.varint()? as usize;
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("The course code is cut short"))?;
+        let text = std::str::from_utf8(&self.bytes[self.offset..end])?.to_string();
+        self.offset = end;
+        Ok(text)
+    }
+}
+
+/// Puts the code of the course being played on the clipboard with CopyCourseCode (Ctrl+C).
+fn copy_course_code(
+    actions: Res<ActionState>,
+    current: Res<CurrentCourse>,
+    courses: Res<Assets<Course>>,
+) {
+    if !actions.just_pressed(Action::CopyCourseCode) {
+        return;
+    }
+
+    let course = match courses.get(&current.handle) {
+        Some(course) => course,
+        None => return,
+    };
+    let code = encode_course(course);
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(code.clone())) {
+        Ok(()) => info!(
+            "Copied the code for {} to the clipboard: {}",
+            course.name, code
+        ),
+        Err(err) => error!("Failed to copy the course code: {}", err),
+    }
+}
+
+/// Replaces the course being played with the course code on the clipboard with PasteCourseCode (Ctrl+V).
+fn paste_course_code(
+    actions: Res<ActionState>,
+    current: Res<CurrentCourse>,
+    mut courses: ResMut<Assets<Course>>,
+) {
+    if !actions.just_pressed(Action::PasteCourseCode) {
+        return;
+    }
+
+    let code = match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(code) => code,
+        Err(err) => {
+            error!("Failed to read the clipboard: {}", err);
+            return;
+        }
+    };
+    let course = match decode_course(&code) {
+        Ok(course) => course,
+        Err(err) => {
+            error!("Failed to read the course code: {:#}", err);
+            return;
+        }
+    };
+
+    // Changing the course asset respawns it's tiles and starts the hole again.
+    if let Some(current_course) = courses.get_mut(&current.handle) {
+        info!("Playing {} from the clipboard", course.name);
+        *current_course = course;
+    }
+}
@@ -1,5 +1,10 @@
-use bevy::{prelude::*, render::mesh::Indices};
+use bevy::{
+    prelude::*,
+    render::mesh::Indices,
+    tasks::{AsyncComputeTaskPool, Task},
+};
 use bevy_rapier3d::prelude::*;
+use futures_lite::future;
 
 pub struct DynamicMeshPlugin;
 impl Plugin for DynamicMeshPlugin {
@@ -10,9 +15,13 @@ impl Plugin for DynamicMeshPlugin {
 }
 
 /// Contains the vertices, uvs and triangles used for building meshes.
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone)]
 pub struct DynamicMesh {
     triangles: Vec<[Vertex; 3]>,
+    /// Triangles that are drawn but don't collide.
+    visual_triangles: Vec<[Vertex; 3]>,
+    /// Triangles that collide but aren't drawn.
+    collision_triangles: Vec<[Vertex; 3]>,
 }
 
 impl DynamicMesh {
@@ -24,15 +33,39 @@ impl DynamicMesh {
     /// Clears the mesh maker.
     pub fn clear(&mut self) {
         self.triangles.clear();
+        self.visual_triangles.clear();
+        self.collision_triangles.clear();
     }
 
     /// Gets the total triangle count.
     pub fn tri_count(&self) -> usize {
-        self.triangles.len()
+        self.triangles.len() + self.visual_triangles.len() + self.collision_triangles.len()
+    }
+
+    /// Gets the count of triangles that end up in the collider.
+    pub fn collider_tri_count(&self) -> usize {
+        self.triangles.len() + self.collision_triangles.len()
     }
 
     /// Inserts a triangle.
     pub fn insert_tri(&mut self, positions: [Vec3; 3]) {
+        let tri = Self::make_tri(positions);
+        self.triangles.push(tri);
+    }
+
+    /// Inserts a triangle that is only drawn.
+    pub fn insert_visual_tri(&mut self, positions: [Vec3; 3]) {
+        let tri = Self::make_tri(positions);
+        self.visual_triangles.push(tri);
+    }
+
+    /// Inserts a triangle that is only used for collisions.
+    pub fn insert_collision_tri(&mut self, positions: [Vec3; 3]) {
+        let tri = Self::make_tri(positions);
+        self.collision_triangles.push(tri);
+    }
+
+    fn make_tri(positions: [Vec3; 3]) -> [Vertex; 3] {
         let normal = (positions[1] - positions[0])
             .cross(positions[2] - positions[0])
             .normalize();
@@ -43,10 +76,24 @@ impl DynamicMesh {
             tri[i] = Vertex {
                 normal: normal,
                 position: positions[i],
-                uv: Vec2::ZERO,
+                uv: Self::box_uv(positions[i], normal),
             };
         }
-        self.triangles.push(tri);
+        tri
+    }
+
+    /// Projects a position onto the side of a box the normal faces the most, one UV per world unit.
+    /// Ground gets it's UVs from above and walls from the side, so textures tile across tiles without seams.
+    fn box_uv(position: Vec3, normal: Vec3) -> Vec2 {
+        let facing = normal.abs();
+        // V points down the texture, so walls have it pointing down the world.
+        if facing.y >= facing.x && facing.y >= facing.z {
+            Vec2::new(position.x, position.z)
+        } else if facing.x >= facing.z {
+            Vec2::new(position.z, -position.y)
+        } else {
+            Vec2::new(position.x, -position.y)
+        }
     }
 
     /// Reduce the vertices and triangles with different rules.
@@ -56,8 +103,14 @@ impl DynamicMesh {
         let mut triangles = Vec::new();
         let mut vertices: Vec<Vertex> = Vec::new();
 
+        // Colliders skip the visual triangles and meshes skip the collision triangles.
+        let extra = match rule {
+            CompareRule::Collider => &self.collision_triangles,
+            CompareRule::Mesh | CompareRule::MeshNoUV => &self.visual_triangles,
+        };
+
         // Go over each vertex in each triangle
-        for triangle in self.triangles.iter() {
+        for triangle in self.triangles.iter().chain(extra.iter()) {
             let mut indices = [0; 3];
             for i in 0..3 {
 
@@ -79,8 +132,8 @@ impl DynamicMesh {
         (triangles, vertices)
     }
 
-    /// Update an existing mesh.
-    pub fn update_mesh(&self, mesh: &mut Mesh) {
+    /// Distils this dynamic mesh into the attributes of a mesh without touching any assets.
+    pub fn mesh_data(&self) -> MeshData {
         let (tris, verts) = self.distil(&CompareRule::Mesh);
         // Migrate our Vec3 position and normal data to [f32; 3] and generate our uvs.
         // TODO: learn how to do this more elegantly like how indices/triangles works.
@@ -90,7 +143,6 @@ impl DynamicMesh {
         for v in verts.iter() {
             vertices.push([v.position.x, v.position.y, v.position.z]);
             normals.push([v.normal.x, v.normal.y, v.normal.z]);
-            // uvs.push([v.position.x, v.position.z]);
             uvs.push([v.uv.x, v.uv.y]);
         }
 
@@ -102,18 +154,12 @@ impl DynamicMesh {
             triangles.push(tri[2]);
         }
 
-        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
-        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
-        // Set our triangles.
-        let indices = Indices::U32(triangles);
-        mesh.set_indices(Some(indices));
-
-        println!(
-            "Updated a mesh with {:?} tris and {:?} verts.",
-            tris.len(),
-            verts.len()
-        );
+        MeshData {
+            positions: vertices,
+            normals,
+            uvs,
+            indices: triangles,
+        }
     }
 
     pub fn collider(&self) -> Collider {
@@ -122,7 +168,7 @@ impl DynamicMesh {
         for v in verts.iter() {
             vertices.push(v.position);
         }
-        println!(
+        debug!(
             "Updated a collider with {:?} tris and {:?} verts.",
             tris.len(),
             vertices.len()
@@ -131,29 +177,93 @@ impl DynamicMesh {
     }
 }
 
-//// Updates a Mesh when the DynamicMesh has been changed.
+/// The distilled attributes of a DynamicMesh.
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    /// Copies our attributes and triangles into an existing mesh.
+    pub fn apply(self, mesh: &mut Mesh) {
+        let tri_count = self.indices.len() / 3;
+        let vert_count = self.positions.len();
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        // Set our triangles.
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+
+        debug!(
+            "Updated a mesh with {:?} tris and {:?} verts.",
+            tri_count, vert_count
+        );
+    }
+}
+
+/// A mesh that is being distilled in the background.
+#[derive(Component)]
+pub struct MeshTask(Task<MeshData>);
+
+/// A collider that is being built in the background.
+#[derive(Component)]
+pub struct ColliderTask(Task<Collider>);
+
+/// Distils a Mesh in the background when the DynamicMesh has been changed.
+/// The old mesh stays in place until the new one is ready.
 fn mesh_on_changed(
+    mut commands: Commands,
     mut assets: ResMut<Assets<Mesh>>,
-    query: Query<(&Handle<Mesh>, &DynamicMesh), Changed<DynamicMesh>>,
+    thread_pool: Res<AsyncComputeTaskPool>,
+    changed_query: Query<(Entity, &DynamicMesh), (Changed<DynamicMesh>, With<Handle<Mesh>>)>,
+    mut task_query: Query<(Entity, &Handle<Mesh>, &mut MeshTask)>,
 ) {
-    for (handle, dynamic) in query.iter() {
-        if let Some(mesh) = assets.get_mut(handle) {
-            dynamic.update_mesh(mesh);
+    // Swap in any meshes that have finished distilling.
+    for (ent, handle, mut task) in task_query.iter_mut() {
+        if let Some(data) = future::block_on(future::poll_once(&mut task.0)) {
+            if let Some(mesh) = assets.get_mut(handle) {
+                data.apply(mesh);
+            }
+            commands.entity(ent).remove::<MeshTask>();
         }
     }
+
+    // Start distilling changed meshes. This replaces (and cancels) any task that's still running.
+    for (ent, dynamic) in changed_query.iter() {
+        let dynamic = dynamic.clone();
+        let task = thread_pool.spawn(async move { dynamic.mesh_data() });
+        commands.entity(ent).insert(MeshTask(task));
+    }
 }
 
-/// Replaces an existing collider with one derived from our changed DynamicMesh.
+/// Builds a new collider in the background when our DynamicMesh has been changed,
+/// replacing the existing collider once it's ready.
 fn collider_on_changed(
     mut commands: Commands,
-    query: Query<(Entity, &DynamicMesh, &Collider), Changed<DynamicMesh>>,
+    thread_pool: Res<AsyncComputeTaskPool>,
+    changed_query: Query<(Entity, &DynamicMesh), (Changed<DynamicMesh>, With<Collider>)>,
+    mut task_query: Query<(Entity, &mut ColliderTask)>,
 ) {
-    for (ent, dynamic, _) in query.iter() {
-        if dynamic.tri_count() > 0 {
+    // Swap in any colliders that have finished building.
+    for (ent, mut task) in task_query.iter_mut() {
+        if let Some(collider) = future::block_on(future::poll_once(&mut task.0)) {
             commands
                 .entity(ent)
+                .remove::<ColliderTask>()
                 .remove::<Collider>()
-                .insert(dynamic.collider());
+                .insert(collider);
+        }
+    }
+
+    // Start building colliders for the changed meshes.
+    for (ent, dynamic) in changed_query.iter() {
+        if dynamic.collider_tri_count() > 0 {
+            let dynamic = dynamic.clone();
+            let task = thread_pool.spawn(async move { dynamic.collider() });
+            commands.entity(ent).insert(ColliderTask(task));
         }
     }
 }
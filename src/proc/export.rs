@@ -0,0 +1,268 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use bevy::prelude::*;
+
+use super::dynamic_mesh::{DynamicMesh, MeshData};
+use super::{
+    course::Course, insert_ground, insert_walls, tile::Tile, tile_definitions::TileDefinitions,
+    CurrentCourse, Ground, Wall, GROUND_COLOUR, WALL_COLOUR,
+};
+use crate::input::{Action, ActionState};
+
+/// Where courses are exported to with ExportCourse (F9).
+const EXPORT_FOLDER: &str = "exports";
+
+/// Exports the course's meshes with ExportCourse (F9).
+pub struct ExportPlugin;
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(export_on_key);
+    }
+}
+
+/// A distilled mesh and the material it's drawn with.
+pub struct ExportMesh {
+    pub name: String,
+    pub colour: Color,
+    pub data: MeshData,
+}
+
+impl ExportMesh {
+    fn new(name: &str, colour: &str, dynamic_mesh: &DynamicMesh) -> Self {
+        Self {
+            name: name.to_string(),
+            colour: Color::hex(colour).unwrap(),
+            data: dynamic_mesh.mesh_data(),
+        }
+    }
+}
+
+/// Builds the ground and wall meshes of a course the same way the game does.
+pub fn course_meshes(tiles: &[Tile], defs: &TileDefinitions) -> Vec<ExportMesh> {
+    let mut ground = DynamicMesh::new();
+    insert_ground(&mut ground, tiles, defs);
+    let mut walls = DynamicMesh::new();
+    insert_walls(&mut walls, tiles, defs);
+
+    vec![
+        ExportMesh::new("ground", GROUND_COLOUR, &ground),
+        ExportMesh::new("walls", WALL_COLOUR, &walls),
+    ]
+}
+
+/// Gets the ground and wall meshes from their dynamic meshes.
+pub fn export_meshes(ground: &DynamicMesh, walls: &DynamicMesh) -> Vec<ExportMesh> {
+    vec![
+        ExportMesh::new("ground", GROUND_COLOUR, ground),
+        ExportMesh::new("walls", WALL_COLOUR, walls),
+    ]
+}
+
+/// Writes the meshes to an .obj file with their materials in a .mtl file next to it.
+pub fn write_obj(meshes: &[ExportMesh], path: &Path) -> anyhow::Result<()> {
+    let mtl_path = path.with_extension("mtl");
+    let mtl_name = mtl_path.file_name().unwrap_or_default().to_string_lossy();
+
+    let mut obj = String::new();
+    let mut mtl = String::new();
+    writeln!(obj, "# Exported from bevy-golf")?;
+    writeln!(obj, "mtllib {}", mtl_name)?;
+
+    // OBJ indices count from 1 across the whole file.
+    let mut offset = 1;
+    for mesh in meshes.iter().filter(|mesh| !mesh.data.indices.is_empty()) {
+        let data = &mesh.data;
+        writeln!(obj, "o {}", mesh.name)?;
+        for [x, y, z] in data.positions.iter() {
+            writeln!(obj, "v {} {} {}", x, y, z)?;
+        }
+        for [u, v] in data.uvs.iter() {
+            // OBJ's V axis points up the texture.
+            writeln!(obj, "vt {} {}", u, 1.0 - v)?;
+        }
+        for [x, y, z] in data.normals.iter() {
+            writeln!(obj, "vn {} {} {}", x, y, z)?;
+        }
+        writeln!(obj, "usemtl {}", mesh.name)?;
+        for tri in data.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|index| index as usize + offset);
+            writeln!(obj, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", a, b, c)?;
+        }
+        offset += data.positions.len();
+
+        let [r, g, b, _] = mesh.colour.as_rgba_f32();
+        writeln!(mtl, "newmtl {}", mesh.name)?;
+        writeln!(mtl, "Kd {} {} {}", r, g, b)?;
+        writeln!(mtl)?;
+    }
+
+    fs::write(path, obj)?;
+    fs::write(mtl_path, mtl)?;
+    Ok(())
+}
+
+/// Writes the meshes to a .gltf file with the buffer embedded in it.
+pub fn write_gltf(meshes: &[ExportMesh], path: &Path) -> anyhow::Result<()> {
+    const FLOAT: u32 = 5126;
+    const UNSIGNED_INT: u32 = 5125;
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut materials = Vec::new();
+    let mut gltf_meshes = Vec::new();
+    let mut nodes = Vec::new();
+
+    // Adds a buffer view and an accessor for it, returning the accessor's index.
+    let mut add_accessor = |bytes: Vec<u8>, target: u32, accessor: String| {
+        views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+            buffer.len(),
+            bytes.len(),
+            target
+        ));
+        buffer.extend(bytes);
+        accessors.push(accessor.replace("VIEW", &(views.len() - 1).to_string()));
+        accessors.len() - 1
+    };
+
+    for mesh in meshes.iter().filter(|mesh| !mesh.data.indices.is_empty()) {
+        let data = &mesh.data;
+        let count = data.positions.len();
+
+        // Positions need their bounds.
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for position in data.positions.iter() {
+            min = min.min(Vec3::from(*position));
+            max = max.max(Vec3::from(*position));
+        }
+        let position = add_accessor(
+            float_bytes(data.positions.iter().flatten()),
+            ARRAY_BUFFER,
+            format!(
+                r#"{{"bufferView":VIEW,"componentType":{},"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+                FLOAT, count, min.x, min.y, min.z, max.x, max.y, max.z
+            ),
+        );
+        let normal = add_accessor(
+            float_bytes(data.normals.iter().flatten()),
+            ARRAY_BUFFER,
+            format!(
+                r#"{{"bufferView":VIEW,"componentType":{},"count":{},"type":"VEC3"}}"#,
+                FLOAT, count
+            ),
+        );
+        let uv = add_accessor(
+            float_bytes(data.uvs.iter().flatten()),
+            ARRAY_BUFFER,
+            format!(
+                r#"{{"bufferView":VIEW,"componentType":{},"count":{},"type":"VEC2"}}"#,
+                FLOAT, count
+            ),
+        );
+        let indices = add_accessor(
+            data.indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect(),
+            ELEMENT_ARRAY_BUFFER,
+            format!(
+                r#"{{"bufferView":VIEW,"componentType":{},"count":{},"type":"SCALAR"}}"#,
+                UNSIGNED_INT,
+                data.indices.len()
+            ),
+        );
+
+        // glTF colours are linear.
+        let [r, g, b, a] = mesh.colour.as_linear_rgba_f32();
+        materials.push(format!(
+            r#"{{"name":"{}","pbrMetallicRoughness":{{"baseColorFactor":[{},{},{},{}],"metallicFactor":0,"roughnessFactor":1}}}}"#,
+            mesh.name, r, g, b, a
+        ));
+        gltf_meshes.push(format!(
+            r#"{{"name":"{}","primitives":[{{"attributes":{{"POSITION":{},"NORMAL":{},"TEXCOORD_0":{}}},"indices":{},"material":{}}}]}}"#,
+            mesh.name,
+            position,
+            normal,
+            uv,
+            indices,
+            materials.len() - 1
+        ));
+        nodes.push(format!(
+            r#"{{"name":"{}","mesh":{}}}"#,
+            mesh.name,
+            gltf_meshes.len() - 1
+        ));
+    }
+
+    let scene_nodes: Vec<String> = (0..nodes.len()).map(|node| node.to_string()).collect();
+    let gltf = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"bevy-golf"}},"scene":0,"scenes":[{{"nodes":[{}]}}],"nodes":[{}],"meshes":[{}],"materials":[{}],"accessors":[{}],"bufferViews":[{}],"buffers":[{{"byteLength":{},"uri":"data:application/octet-stream;base64,{}"}}]}}"#,
+        scene_nodes.join(","),
+        nodes.join(","),
+        gltf_meshes.join(","),
+        materials.join(","),
+        accessors.join(","),
+        views.join(","),
+        buffer.len(),
+        base64::encode(&buffer)
+    );
+
+    fs::write(path, gltf)?;
+    Ok(())
+}
+
+/// Writes the meshes to both a .gltf and an .obj file, named after `path` without it's extension.
+pub fn write_all(meshes: &[ExportMesh], path: &Path) -> anyhow::Result<()> {
+    if let Some(folder) = path.parent() {
+        fs::create_dir_all(folder)?;
+    }
+    write_gltf(meshes, &path.with_extension("gltf"))?;
+    write_obj(meshes, &path.with_extension("obj"))?;
+    Ok(())
+}
+
+/// Gets a file name for a course from it's name.
+pub fn export_name(course: &str) -> String {
+    course
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+/// Exports the ground and walls to glTF and OBJ, so artists can decorate the course in Blender.
+fn export_on_key(
+    actions: Res<ActionState>,
+    current: Res<CurrentCourse>,
+    courses: Res<Assets<Course>>,
+    ground_query: Query<&DynamicMesh, With<Ground>>,
+    wall_query: Query<&DynamicMesh, With<Wall>>,
+) {
+    if !actions.just_pressed(Action::ExportCourse) {
+        return;
+    }
+
+    let (ground, walls) = match (ground_query.get_single(), wall_query.get_single()) {
+        (Ok(ground), Ok(walls)) => (ground, walls),
+        _ => return,
+    };
+    let name = courses
+        .get(&current.handle)
+        .map_or("course".to_string(), |course| export_name(&course.name));
+
+    let path = Path::new(EXPORT_FOLDER).join(name);
+    match write_all(&export_meshes(ground, walls), &path) {
+        Ok(()) => info!("Exported the course to {}", path.display()),
+        Err(err) => error!("Failed to export the course: {}", err),
+    }
+}
+
+fn float_bytes<'a>(floats: impl Iterator<Item = &'a f32>) -> Vec<u8> {
+    floats.flat_map(|float| float.to_le_bytes()).collect()
+}
@@ -0,0 +1,290 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail};
+use bevy::{prelude::*, utils::HashMap};
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+
+use super::{
+    autotile::AutoTiler,
+    course::{Course, Tee},
+    migrate::COURSE_VERSION,
+    tile::Orientation,
+    tile_definitions::TileDefinitions,
+};
+
+/// The shape of the holes the generator makes.
+#[derive(Debug, Clone)]
+pub struct GeneratorSettings {
+    /// How many tiles the path from the tee to the cup has.
+    pub length: usize,
+    /// How many times the path turns left or right.
+    pub turns: usize,
+    /// How many times the path goes up or down a step.
+    pub elevation_changes: usize,
+    /// The chance of each tile beside the path being a hazard, from 0 to 1.
+    pub hazard_density: f64,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            length: 12,
+            turns: 2,
+            elevation_changes: 1,
+            hazard_density: 0.1,
+        }
+    }
+}
+
+/// Gets the seed for today's hole, the same for everyone no matter where they are.
+pub fn daily_seed() -> u64 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    since_epoch.as_secs() / (60 * 60 * 24)
+}
+
+/// The generator's random numbers.
+/// ChaCha8 gives the same numbers for a seed in every release, unlike rand's StdRng, and the ways
+/// the numbers are used are written here instead of using rand's so they can't change either.
+/// This keeps the daily hole the same for everyone, whatever version of the game they have.
+struct Dice(ChaCha8Rng);
+
+impl Dice {
+    fn new(seed: u64) -> Self {
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        Dice(ChaCha8Rng::from_seed(bytes))
+    }
+
+    /// Gets a number from 0 up to (but not including) a maximum, every number is as likely.
+    fn below(&mut self, max: u32) -> u32 {
+        // Numbers past the last whole multiple of max are thrown away, so the smaller numbers aren't more likely.
+        let limit = u32::MAX - u32::MAX % max;
+        loop {
+            let number = self.0.next_u32();
+            if number < limit {
+                return number % max;
+            }
+        }
+    }
+
+    /// Whether something with a chance from 0 to 1 happens.
+    fn chance(&mut self, chance: f64) -> bool {
+        // The top 53 bits fill a double's mantissa.
+        let roll = (self.0.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        roll < chance
+    }
+
+    /// Shuffles a list so every order is as likely.
+    fn shuffle<T>(&mut self, list: &mut [T]) {
+        for i in (1..list.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            list.swap(i, j);
+        }
+    }
+}
+
+/// The directions the path can head in, turning right goes to the next one.
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Generates a hole from a seed. The same seed and settings always make the same course.
+/// The path is walked from the tee, turning and changing height at random points,
+/// then hazards are scattered beside it and the tiles are picked by the AutoTiler.
+pub fn generate_course(
+    seed: u64,
+    settings: &GeneratorSettings,
+    defs: &TileDefinitions,
+) -> anyhow::Result<Course> {
+    if settings.length < 4 {
+        bail!("Holes need to be at least 4 tiles long");
+    }
+
+    let mut dice = Dice::new(seed);
+    let auto_tiler = AutoTiler::new(defs);
+    let flat_type = auto_tiler
+        .tile(IVec3::ZERO, [true; 4])
+        .map(|tile| tile.tile_type)
+        .ok_or_else(|| anyhow!("There's no flat tile definition"))?;
+    let cup_type = defs
+        .defs
+        .iter()
+        .find(|def| def.cup)
+        .map(|def| def.key.clone())
+        .ok_or_else(|| anyhow!("There's no cup tile definition"))?;
+    let hazard_type = defs
+        .defs
+        .iter()
+        .find(|def| def.hazard)
+        .map(|def| def.key.clone());
+
+    let path = walk_path(&mut dice, settings);
+    if path.len() < 4 {
+        bail!("The path got stuck after {} tiles", path.len());
+    }
+    if path.len() < settings.length {
+        warn!(
+            "The path got stuck after {} of {} tiles",
+            path.len(),
+            settings.length
+        );
+    }
+    let path_heights = path_heights(&mut dice, &path, settings.elevation_changes);
+
+    let mut heights = HashMap::default();
+    let mut fixed = HashMap::default();
+    for (cell, height) in path.iter().zip(path_heights.iter()) {
+        heights.insert(*cell, *height);
+    }
+    let tee = path[0];
+    let cup = path[path.len() - 1];
+    fixed.insert(tee, flat_type);
+    fixed.insert(cup, cup_type);
+
+    // Hazards only go beside parts of the path that are level, so they join up without walls.
+    if let Some(hazard_type) = hazard_type {
+        for cell in path.iter() {
+            for (x, z) in DIRECTIONS {
+                let side = *cell + IVec2::new(x, z);
+                if heights.contains_key(&side) || !dice.chance(settings.hazard_density) {
+                    continue;
+                }
+                let touching: Vec<i32> = surrounding(side)
+                    .iter()
+                    .filter_map(|cell| heights.get(cell).copied())
+                    .collect();
+                let height = heights[cell];
+                if touching.iter().all(|touching| *touching == height) {
+                    heights.insert(side, height);
+                    fixed.insert(side, hazard_type.clone());
+                }
+            }
+        }
+    }
+
+    // Keep the course from going below the ground of the scene.
+    let lowest = heights.values().copied().min().unwrap_or_default();
+    let heights: HashMap<IVec2, i32> = heights
+        .into_iter()
+        .map(|(cell, height)| (cell, height - lowest))
+        .collect();
+
+    // The path always sets off along the first direction.
+    let facing = Orientation::North;
+
+    Ok(Course {
+        version: COURSE_VERSION,
+        name: format!("Generated #{}", seed),
+        par: None,
+        tee: Tee {
+            position: IVec3::new(tee.x, heights[&tee], tee.y),
+            facing,
+        },
+        tiles: auto_tiler.tile_grid(&heights, &fixed),
+    })
+}
+
+/// Walks the path from the tee, turning at random points along it.
+/// The path never touches itself, so it stops early when it has nowhere to go.
+fn walk_path(dice: &mut Dice, settings: &GeneratorSettings) -> Vec<IVec2> {
+    // Turns don't happen on the tee, cup, or right next to another turn.
+    let mut turn_at: Vec<usize> = (2..settings.length - 2).step_by(2).collect();
+    dice.shuffle(&mut turn_at);
+    turn_at.truncate(settings.turns);
+
+    let mut path = vec![IVec2::ZERO];
+    let mut direction = 0;
+    while path.len() < settings.length {
+        let current = path[path.len() - 1];
+
+        let mut choices = match turn_at.contains(&path.len()) {
+            true => match dice.chance(0.5) {
+                true => vec![1, 3, 0],
+                false => vec![3, 1, 0],
+            },
+            false => vec![0, 1, 3],
+        };
+        choices.retain(|turn| {
+            let (x, z) = DIRECTIONS[(direction + turn) % 4];
+            let next = current + IVec2::new(x, z);
+            // Only the current cell can be next to (or diagonal to) the next one.
+            surrounding(next)
+                .iter()
+                .chain([next].iter())
+                .all(|cell| *cell == current || !path.contains(cell) || is_behind(&path, *cell))
+        });
+
+        match choices.first() {
+            Some(turn) => {
+                direction = (direction + turn) % 4;
+                let (x, z) = DIRECTIONS[direction];
+                path.push(current + IVec2::new(x, z));
+            }
+            None => break,
+        }
+    }
+
+    path
+}
+
+/// Whether a cell is the one before the end of the path, which is diagonal to the next cell after a turn.
+fn is_behind(path: &[IVec2], cell: IVec2) -> bool {
+    path.len() >= 2 && path[path.len() - 2] == cell
+}
+
+/// Picks the height of each cell on the path.
+/// Steps up or down only happen partway along straight parts, so the AutoTiler can make them ramps.
+fn path_heights(dice: &mut Dice, path: &[IVec2], changes: usize) -> Vec<i32> {
+    // A change at i is between path[i] and path[i + 1].
+    let mut possible: Vec<usize> = (1..path.len().saturating_sub(3))
+        .filter(|i| {
+            let step = path[*i + 1] - path[*i];
+            path[*i] - path[*i - 1] == step && path[*i + 2] - path[*i + 1] == step
+        })
+        .collect();
+    dice.shuffle(&mut possible);
+
+    let mut chosen: Vec<usize> = Vec::new();
+    for i in possible {
+        if chosen.len() == changes {
+            break;
+        }
+        // Keep some flat ground between steps.
+        if chosen
+            .iter()
+            .all(|other| (*other as i32 - i as i32).abs() >= 2)
+        {
+            chosen.push(i);
+        }
+    }
+
+    let mut heights = vec![0; path.len()];
+    for i in 1..path.len() {
+        heights[i] = heights[i - 1];
+        if chosen.contains(&(i - 1)) {
+            heights[i] += match dice.chance(0.5) {
+                true => 1,
+                false => -1,
+            };
+        }
+    }
+    heights
+}
+
+/// Gets the 8 cells around a cell.
+fn surrounding(cell: IVec2) -> [IVec2; 8] {
+    [
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+    ]
+    .map(|(x, z)| cell + IVec2::new(x, z))
+}
@@ -0,0 +1,191 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+use bevy::{prelude::*, utils::HashMap};
+use image::{GrayImage, RgbImage};
+
+use super::{
+    autotile::AutoTiler,
+    course::{Course, Tee},
+    migrate::COURSE_VERSION,
+    tile::Orientation,
+    tile_definitions::{TileDefinition, TileDefinitions},
+};
+
+/// How many TILE_BOUNDS.y steps a white pixel is above a black one, when it isn't given.
+pub const DEFAULT_LEVELS: u32 = 4;
+
+/// What a pixel of a heightmap's mask marks the cell as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marking {
+    Ground,
+    /// There's no tile in the cell.
+    Empty,
+    Tee,
+    Cup,
+    Hazard,
+}
+
+impl Marking {
+    /// Black is empty, red is the cup, green is the tee and blue is a hazard. Anything else is ground.
+    pub fn from_colour([r, g, b]: [u8; 3]) -> Self {
+        let high = |c: u8| c >= 128;
+        match (high(r), high(g), high(b)) {
+            _ if r < 64 && g < 64 && b < 64 => Marking::Empty,
+            (true, false, false) => Marking::Cup,
+            (false, true, false) => Marking::Tee,
+            (false, false, true) => Marking::Hazard,
+            _ => Marking::Ground,
+        }
+    }
+}
+
+/// Builds a course from a greyscale heightmap, one tile for each pixel.
+/// Pixels go along X and rows go along Z, the brighter a pixel the higher it's tile,
+/// with white being `levels` steps above black.
+/// The mask is an image the same size that marks the tee, cup, hazards and empty cells.
+pub fn import_heightmap(
+    name: &str,
+    heightmap: &GrayImage,
+    mask: Option<&RgbImage>,
+    levels: u32,
+    defs: &TileDefinitions,
+) -> anyhow::Result<Course> {
+    if let Some(mask) = mask {
+        if mask.dimensions() != heightmap.dimensions() {
+            bail!(
+                "The mask is {:?} but the heightmap is {:?}, they need to be the same size",
+                mask.dimensions(),
+                heightmap.dimensions()
+            );
+        }
+    }
+
+    let auto_tiler = AutoTiler::new(defs);
+    let flat_type = auto_tiler
+        .tile(IVec3::ZERO, [true; 4])
+        .map(|tile| tile.tile_type)
+        .ok_or_else(|| anyhow!("There's no flat tile definition"))?;
+    let type_where = |is: fn(&TileDefinition) -> bool, what: &str| {
+        defs.defs
+            .iter()
+            .find(|def| is(def))
+            .map(|def| def.key.clone())
+            .ok_or_else(|| {
+                anyhow!(
+                    "The mask marks a {} but there's no {} tile definition",
+                    what,
+                    what
+                )
+            })
+    };
+
+    let mut heights = HashMap::default();
+    let mut fixed = HashMap::default();
+    let mut tee = None;
+    let mut cup = None;
+
+    for (x, z, pixel) in heightmap.enumerate_pixels() {
+        let cell = IVec2::new(x as i32, z as i32);
+        let marking = mask.map_or(Marking::Ground, |mask| {
+            Marking::from_colour(mask.get_pixel(x, z).0)
+        });
+
+        let tile_type = match marking {
+            Marking::Empty => continue,
+            Marking::Ground => None,
+            Marking::Tee => {
+                match tee {
+                    Some(_) => warn!("The mask marks more than one tee, using the first"),
+                    None => tee = Some(cell),
+                }
+                Some(flat_type.clone())
+            }
+            Marking::Cup => {
+                cup = Some(cell);
+                Some(type_where(|def| def.cup, "cup")?)
+            }
+            Marking::Hazard => Some(type_where(|def| def.hazard, "hazard")?),
+        };
+
+        let height = (pixel.0[0] as f32 / 255.0 * levels as f32).round() as i32;
+        heights.insert(cell, height);
+        if let Some(tile_type) = tile_type {
+            fixed.insert(cell, tile_type);
+        }
+    }
+
+    if heights.is_empty() {
+        bail!("The mask leaves no cells for the course");
+    }
+    if cup.is_none() {
+        warn!("The course has no cup, mark one in red on the mask");
+    }
+    let tee = match tee {
+        Some(tee) => tee,
+        None => {
+            // Fall back to the first cell, and make sure it's flat for the balls to sit on.
+            let first = *heights.keys().min_by_key(|cell| (cell.y, cell.x)).unwrap();
+            warn!(
+                "The course has no tee, mark one in green on the mask. Using {}",
+                first
+            );
+            fixed.insert(first, flat_type);
+            first
+        }
+    };
+
+    Ok(Course {
+        version: COURSE_VERSION,
+        name: name.to_string(),
+        par: None,
+        tee: Tee {
+            position: IVec3::new(tee.x, heights[&tee], tee.y),
+            facing: cup.map_or(Orientation::North, |cup| facing(cup - tee)),
+        },
+        tiles: auto_tiler.tile_grid(&heights, &fixed),
+    })
+}
+
+/// Reads a heightmap, and optionally a mask, from image files and builds a course from them.
+/// The course is named after the heightmap's file.
+pub fn import_heightmap_files(
+    heightmap: &Path,
+    mask: Option<&Path>,
+    levels: u32,
+    defs: &TileDefinitions,
+) -> anyhow::Result<Course> {
+    let heights = image::open(heightmap)
+        .with_context(|| format!("Failed to read heightmap {}", heightmap.display()))?
+        .to_luma8();
+    let mask = match mask {
+        Some(mask) => Some(
+            image::open(mask)
+                .with_context(|| format!("Failed to read mask {}", mask.display()))?
+                .to_rgb8(),
+        ),
+        None => None,
+    };
+
+    let name = heightmap
+        .file_stem()
+        .map_or("Heightmap".to_string(), |stem| {
+            stem.to_string_lossy().to_string()
+        });
+    import_heightmap(&name, &heights, mask.as_ref(), levels, defs)
+}
+
+/// Gets the orientation that faces closest to a direction on the grid.
+fn facing(direction: IVec2) -> Orientation {
+    if direction.x.abs() > direction.y.abs() {
+        match direction.x > 0 {
+            true => Orientation::East,
+            false => Orientation::West,
+        }
+    } else {
+        match direction.y > 0 {
+            true => Orientation::South,
+            false => Orientation::North,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{anyhow, bail, Context};
+use bevy::prelude::*;
+
+use super::tile::Edge;
+
+/// Reads the triangles out of an OBJ or binary glTF (.glb) file.
+/// Positions are kept in the mesh's own space, node transforms are ignored.
+pub fn load_triangles(path: &str, bytes: &[u8]) -> anyhow::Result<Vec<[Vec3; 3]>> {
+    let extension = path.rsplit('.').next().unwrap_or_default().to_lowercase();
+    let triangles = match extension.as_str() {
+        "obj" => parse_obj(std::str::from_utf8(bytes)?),
+        "glb" => parse_glb(bytes),
+        "gltf" => Err(anyhow!("glTF meshes must be exported as binary .glb")),
+        _ => Err(anyhow!("Unsupported mesh format '{}'", extension)),
+    };
+    triangles.with_context(|| format!("Failed to load tile mesh {}", path))
+}
+
+/// Finds the edges that only belong to one triangle.
+pub fn boundary_edges(triangles: &[[Vec3; 3]]) -> Vec<[Vec3; 2]> {
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut edges_count = Vec::new();
+
+    for triangle in triangles {
+        for i in 0..3 {
+            let edge = Edge(triangle[i], triangle[(i + 1) % 3]);
+            match edges.iter().position(|x| edge.eq(x)) {
+                Some(index) => edges_count[index] += 1,
+                None => {
+                    edges.push(edge);
+                    edges_count.push(1);
+                }
+            }
+        }
+    }
+
+    edges
+        .iter()
+        .zip(edges_count)
+        .filter(|(_, count)| *count == 1)
+        .map(|(edge, _)| [edge.0, edge.1])
+        .collect()
+}
+
+fn parse_obj(text: &str) -> anyhow::Result<Vec<[Vec3; 3]>> {
+    let mut positions = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => {
+                let coords = parts
+                    .take(3)
+                    .map(str::parse)
+                    .collect::<Result<Vec<f32>, _>>()
+                    .with_context(|| format!("Bad vertex on line {}", number + 1))?;
+                if coords.len() < 3 {
+                    bail!("Vertex on line {} needs 3 coordinates", number + 1);
+                }
+                positions.push(Vec3::new(coords[0], coords[1], coords[2]));
+            }
+            Some("f") => {
+                let face = parts
+                    .map(|part| obj_index(part, positions.len()))
+                    .collect::<anyhow::Result<Vec<usize>>>()
+                    .with_context(|| format!("Bad face on line {}", number + 1))?;
+
+                // Triangulate the face as a fan.
+                for i in 2..face.len() {
+                    triangles.push([
+                        positions[face[0]],
+                        positions[face[i - 1]],
+                        positions[face[i]],
+                    ]);
+                }
+            }
+            // We don't care about normals, uvs, materials or groups.
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+/// Turns a face vertex ("1", "1/2", "1/2/3" or "1//3") into an index of our positions.
+fn obj_index(part: &str, count: usize) -> anyhow::Result<usize> {
+    let index: i64 = part.split('/').next().unwrap_or_default().parse()?;
+
+    // OBJ indices start at 1 and negative indices count back from the last vertex.
+    let index = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+
+    if index < 0 || index >= count as i64 {
+        bail!("Vertex index {} is out of range", part);
+    }
+    Ok(index as usize)
+}
+
+fn parse_glb(bytes: &[u8]) -> anyhow::Result<Vec<[Vec3; 3]>> {
+    let gltf = gltf::Gltf::from_slice(bytes)?;
+    let blob = gltf.blob.as_deref();
+    let mut triangles = Vec::new();
+
+    for mesh in gltf.meshes() {
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+
+            // Only the binary chunk of the .glb can be read.
+            let reader = primitive.reader(|buffer| match buffer.source() {
+                gltf::buffer::Source::Bin => blob,
+                gltf::buffer::Source::Uri(_) => None,
+            });
+
+            let positions: Vec<Vec3> = reader
+                .read_positions()
+                .ok_or_else(|| anyhow!("Mesh primitive has no readable positions"))?
+                .map(Vec3::from)
+                .collect();
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+
+            for tri in indices.chunks_exact(3) {
+                let get = |i: u32| {
+                    positions
+                        .get(i as usize)
+                        .copied()
+                        .ok_or_else(|| anyhow!("Index {} is out of range", i))
+                };
+                triangles.push([get(tri[0])?, get(tri[1])?, get(tri[2])?]);
+            }
+        }
+    }
+
+    Ok(triangles)
+}
@@ -0,0 +1,235 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail};
+use bevy::prelude::*;
+use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize};
+
+use super::{
+    course::{Course, Tee},
+    tile::{Flip, Orientation, Tile},
+    tile_definitions::TileDefinition,
+};
+
+/// The version course files are written with.
+pub const COURSE_VERSION: u32 = 1;
+/// The version tile pack files are written with.
+pub const TILE_PACK_VERSION: u32 = 1;
+
+/// An old version of a file that can be upgraded to the next version.
+/// Versions are chained, so a file is upgraded one version at a time until it's the latest.
+pub trait Migrate {
+    type Next;
+
+    fn migrate(self) -> anyhow::Result<Self::Next>;
+}
+
+/// A file after it has been upgraded to the latest version.
+pub struct Migrated<T> {
+    pub value: T,
+    /// The version the file was written with.
+    pub from_version: u32,
+}
+
+impl<T> Migrated<T> {
+    /// Whether the file was written with an older version and needs rewriting.
+    pub fn outdated(&self, latest: u32) -> bool {
+        self.from_version < latest
+    }
+}
+
+/// Reads a course file written by any version of the game.
+pub fn read_course(bytes: &[u8]) -> anyhow::Result<Migrated<Course>> {
+    let from_version = file_version(bytes)?;
+    let value = match from_version {
+        0 => ron::de::from_bytes::<CourseV0>(bytes)?.migrate()?,
+        COURSE_VERSION => ron::de::from_bytes::<Course>(bytes)?,
+        version => bail!(
+            "The course is version {} but the newest this game knows is {}",
+            version,
+            COURSE_VERSION
+        ),
+    };
+    Ok(Migrated {
+        value,
+        from_version,
+    })
+}
+
+/// Reads a tile pack written by any version of the game.
+pub fn read_tile_pack(bytes: &[u8]) -> anyhow::Result<Migrated<Vec<TileDefinition>>> {
+    let from_version = file_version(bytes)?;
+    let value = match from_version {
+        0 => ron::de::from_bytes::<Vec<TileDefinitionV0>>(bytes)?
+            .into_iter()
+            .map(Migrate::migrate)
+            .collect::<anyhow::Result<_>>()?,
+        TILE_PACK_VERSION => ron::de::from_bytes::<TilePackFile>(bytes)?.tiles,
+        version => bail!(
+            "The tile pack is version {} but the newest this game knows is {}",
+            version,
+            TILE_PACK_VERSION
+        ),
+    };
+    Ok(Migrated {
+        value,
+        from_version,
+    })
+}
+
+/// Writes a tile pack file with the latest version.
+pub fn save_tile_pack(tiles: &[TileDefinition], path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let file = TilePackFile {
+        version: TILE_PACK_VERSION,
+        tiles: tiles.to_vec(),
+    };
+    let config = ron::ser::PrettyConfig::new().depth_limit(3);
+    fs::write(path, ron::ser::to_string_pretty(&file, config)?)?;
+    Ok(())
+}
+
+/// Gets the version a file was written with.
+/// Files from before versions were added (without a version, or tile packs that were a bare list) are version 0.
+/// Files that can't be read at all are an error, rather than being read as version 0.
+fn file_version(bytes: &[u8]) -> anyhow::Result<u32> {
+    #[derive(Deserialize)]
+    struct VersionProbe {
+        #[serde(default, deserialize_with = "present")]
+        version: Option<u32>,
+    }
+
+    /// Reads a version that's there, so a missing version can be told apart from a broken one.
+    fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+        u32::deserialize(deserializer).map(Some)
+    }
+
+    match ron::de::from_bytes::<VersionProbe>(bytes) {
+        Ok(probe) => Ok(probe.version.unwrap_or(0)),
+        Err(err) => match ron::de::from_bytes::<Vec<IgnoredAny>>(bytes) {
+            Ok(_) => Ok(0),
+            Err(_) => Err(err.into()),
+        },
+    }
+}
+
+/// The layout of a tile pack file.
+#[derive(Deserialize, Serialize)]
+struct TilePackFile {
+    version: u32,
+    tiles: Vec<TileDefinition>,
+}
+
+/// The keys of the tile types from when they were numbered, the first is tile type 1.
+const NUMBERED_TILE_KEYS: [&str; 6] = [
+    "base:flat",
+    "base:hole",
+    "base:ramp",
+    "base:corner",
+    "base:ramp_corner",
+    "base:ramp_corner_alt",
+];
+
+fn numbered_tile_key(id: u32) -> anyhow::Result<String> {
+    (id as usize)
+        .checked_sub(1)
+        .and_then(|index| NUMBERED_TILE_KEYS.get(index))
+        .map(|key| key.to_string())
+        .ok_or_else(|| anyhow!("There's no tile type numbered {}", id))
+}
+
+/// Courses from before they had versions, when tile types could be numbers.
+#[derive(Deserialize)]
+struct CourseV0 {
+    name: String,
+    #[serde(default)]
+    par: Option<u32>,
+    tee: Tee,
+    tiles: Vec<TileV0>,
+}
+
+#[derive(Deserialize)]
+struct TileV0 {
+    position: IVec3,
+    rotation: Orientation,
+    #[serde(default)]
+    flip: Flip,
+    tile_type: TileTypeV0,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TileTypeV0 {
+    Numbered(u32),
+    Key(String),
+}
+
+impl Migrate for CourseV0 {
+    type Next = Course;
+
+    fn migrate(self) -> anyhow::Result<Course> {
+        let tiles = self
+            .tiles
+            .into_iter()
+            .map(|tile| {
+                Ok(Tile {
+                    position: tile.position,
+                    rotation: tile.rotation,
+                    flip: tile.flip,
+                    tile_type: match tile.tile_type {
+                        TileTypeV0::Numbered(id) => numbered_tile_key(id)?,
+                        TileTypeV0::Key(key) => key,
+                    },
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Course {
+            version: COURSE_VERSION,
+            name: self.name,
+            par: self.par,
+            tee: self.tee,
+            tiles,
+        })
+    }
+}
+
+/// Tile definitions from before tile packs had versions, when they could be numbered instead of keyed.
+#[derive(Deserialize)]
+struct TileDefinitionV0 {
+    #[serde(default)]
+    id: Option<u32>,
+    #[serde(default)]
+    key: Option<String>,
+    perimeter: Vec<u8>,
+    name: String,
+    #[serde(default)]
+    mesh: Option<String>,
+    #[serde(default)]
+    collider: Option<String>,
+    #[serde(default)]
+    cup: bool,
+    #[serde(default)]
+    hazard: bool,
+}
+
+impl Migrate for TileDefinitionV0 {
+    type Next = TileDefinition;
+
+    fn migrate(self) -> anyhow::Result<TileDefinition> {
+        let key = match (self.key, self.id) {
+            (Some(key), _) => key,
+            (None, Some(id)) => numbered_tile_key(id)?,
+            (None, None) => bail!("Tile definition \"{}\" has no key", self.name),
+        };
+
+        Ok(TileDefinition {
+            key,
+            perimeter: self.perimeter,
+            name: self.name,
+            mesh: self.mesh,
+            collider: self.collider,
+            cup: self.cup,
+            hazard: self.hazard,
+            ..default()
+        })
+    }
+}
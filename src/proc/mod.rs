@@ -1,14 +1,30 @@
+use std::{fs, ops::Deref, path::Path};
+
+use anyhow::Context;
+
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+use course::*;
 use tile::*;
 use tile_definitions::*;
 
+pub mod autotile;
+pub mod course;
+pub mod course_code;
 mod dynamic_mesh;
+pub mod export;
+pub mod generator;
+pub mod heightmap;
+mod mesh_asset;
+pub mod migrate;
 pub mod tile_definitions;
 pub mod tile;
+pub mod terrain_brush;
+pub mod tile_graph;
+pub mod wall_cutaway;
 // use self::mesh_maker::MeshMaker;
-use self::dynamic_mesh::{DynamicMesh, DynamicMeshPlugin};
+use self::dynamic_mesh::{ColliderTask, DynamicMesh, DynamicMeshPlugin};
 
 pub struct ProcPlugin;
 
@@ -16,95 +32,207 @@ impl Plugin for ProcPlugin {
     fn build(&self, app: &mut App) {
         app.add_asset::<TileDefinitions>();
         app.init_asset_loader::<TileDefinitionsLoader>();
+        app.add_asset::<Course>();
+        app.init_asset_loader::<CourseLoader>();
+        app.init_resource::<TilePacks>();
+        app.init_resource::<TileRegistry>();
+        app.init_resource::<CoursePath>();
+        app.init_resource::<CurrentCourse>();
         app.add_plugin(DynamicMeshPlugin);
+        app.add_startup_system(load_tile_packs);
+        app.add_startup_system(load_course);
         app.add_startup_system(add_ground);
         app.add_startup_system(add_walls);
         app.add_event::<UpdateGroundEvent>();
+        app.add_event::<NewHoleEvent>();
+        // Tiles are spawned before the update stage so the ground is built with them.
+        app.add_system_to_stage(CoreStage::PreUpdate, spawn_course);
         app.add_system(update_ground);
         app.add_system(update_walls);
-        app.add_system(reload_tile_defs);
-        app.add_startup_system(add_tiles);
+        app.add_system(rebuild_tile_registry);
+        // Start holes before the balls and replay clock see the frame, so they always agree on when it began.
+        app.add_system_to_stage(CoreStage::PreUpdate, start_hole);
+    }
+}
+
+/// The folder tile packs are loaded from.
+const TILE_PACK_FOLDER: &str = "tiles";
+/// The pack that's merged before all others, so it wins any conflicts.
+const BASE_TILE_PACK: &str = "tiles/base.ron";
+
+/// Handles to every tile pack in the TILE_PACK_FOLDER.
+#[derive(Default)]
+pub struct TilePacks(Vec<HandleUntyped>);
+
+/// The tile definitions of every loaded tile pack merged together.
+#[derive(Default)]
+pub struct TileRegistry(pub TileDefinitions);
+
+impl Deref for TileRegistry {
+    type Target = TileDefinitions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// The course that's played when the game starts.
+const FIRST_COURSE: &str = "courses/first.course.ron";
+
+/// The path of the course to play, relative to the assets folder.
+pub struct CoursePath(pub String);
+
+impl Default for CoursePath {
+    fn default() -> Self {
+        CoursePath(FIRST_COURSE.to_string())
     }
 }
 
+/// The course being played.
+pub struct CurrentCourse {
+    pub handle: Handle<Course>,
+    /// The tee of the course, once it has loaded.
+    pub tee: Option<Tee>,
+    hole: HoleState,
+    /// Set when the course asset is changed in place, like by the terrain brush,
+    /// so it's tiles are respawned without restarting the hole.
+    edited: bool,
+}
+
+impl Default for CurrentCourse {
+    fn default() -> Self {
+        CurrentCourse {
+            handle: Handle::default(),
+            tee: None,
+            hole: HoleState::WaitingForGround,
+            edited: false,
+        }
+    }
+}
+
+impl CurrentCourse {
+    /// Whether the ground is ready and the hole can be played.
+    pub fn started(&self) -> bool {
+        matches!(self.hole, HoleState::Started)
+    }
+}
+
+/// Holes wait for the ground's collider to be built before they start,
+/// so balls aren't dropped onto nothing.
+enum HoleState {
+    WaitingForGround,
+    BuildingGround,
+    Started,
+}
+
+/// Sent when a course's ground is ready and the balls should go to it's tee.
+pub struct NewHoleEvent(pub Tee);
+
+/// The colours of the ground and wall materials.
+const GROUND_COLOUR: &str = "1A7525";
+const WALL_COLOUR: &str = "E8B792";
+
 #[derive(Component)]
 pub struct Ground;
 #[derive(Component)]
 pub struct Wall;
 
-fn add_tiles(mut commands: Commands, mut ev_update_ground: EventWriter<UpdateGroundEvent>) {
-    commands.spawn().insert(Tile {
-        position: IVec3::ZERO,
-        rotation: Orientation::North,
-        tile_type: 1
-    });
-    commands.spawn().insert(Tile {
-        position: IVec3::new(2, 1, 0),
-        rotation: Orientation::North,
-        tile_type: 1,
-    });
-    commands.spawn().insert(Tile {
-        position: IVec3::new(1, 1, 0),
-        rotation: Orientation::North,
-        tile_type: 3,
-    });
-    commands.spawn().insert(Tile {
-        position: IVec3::new(0, 0, -1),
-        rotation: Orientation::North,
-        tile_type: 6,
-    });
-    commands.spawn().insert(Tile {
-        position: IVec3::new(1, 0, -1),
-        rotation: Orientation::North,
-        tile_type: 1,
-    });
-    commands.spawn().insert(Tile {
-        position: IVec3::new(2, 0, -1),
-        rotation: Orientation::North,
-        tile_type: 1,
-    });
-    commands.spawn().insert(Tile {
-        position: IVec3::new(-1, 0, 0),
-        rotation: Orientation::West,
-        tile_type: 4,
-    });
-
-    commands.spawn().insert(Tile {
-        position: IVec3::new(-1, 0, -1),
-        rotation: Orientation::East,
-        tile_type: 3,
-    });
-
-    commands.spawn().insert(Tile {
-        position: IVec3::new(-1, -1, -2),
-        rotation: Orientation::East,
-        tile_type: 4,
-    });
-
-    commands.spawn().insert(Tile {
-        position: IVec3::new(-2, -1, -2),
-        rotation: Orientation::North,
-        tile_type: 2,
-    });
+fn load_course(
+    mut current: ResMut<CurrentCourse>,
+    asset_server: Res<AssetServer>,
+    path: Res<CoursePath>,
+) {
+    // Courses that were added before the app started, like the simulations' in memory courses, are kept.
+    if current.handle != Handle::default() {
+        return;
+    }
+    current.handle = asset_server.load(path.0.as_str());
+}
+
+/// Replaces the tiles in the world with the current course's tiles whenever it loads or changes.
+fn spawn_course(
+    mut commands: Commands,
+    mut ev_assets: EventReader<AssetEvent<Course>>,
+    mut ev_update_ground: EventWriter<UpdateGroundEvent>,
+    mut current: ResMut<CurrentCourse>,
+    courses: Res<Assets<Course>>,
+    tile_query: Query<Entity, With<Tile>>,
+) {
+    for ev in ev_assets.iter() {
+        let handle = match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle: _ } => continue,
+        };
 
-    ev_update_ground.send_default();
+        // Only the course being played is spawned.
+        if *handle != current.handle {
+            continue;
+        }
+        let course = match courses.get(handle) {
+            Some(course) => course,
+            None => continue,
+        };
+
+        for ent in tile_query.iter() {
+            commands.entity(ent).despawn();
+        }
+        for tile in course.tiles.iter() {
+            commands.spawn().insert(tile.clone());
+        }
+
+        // Edits keep the balls where they are and the turn as it is.
+        if current.edited {
+            current.edited = false;
+        } else {
+            current.tee = Some(course.tee);
+            current.hole = HoleState::WaitingForGround;
+        }
+        ev_update_ground.send_default();
+    }
+}
+
+/// Starts the hole once the ground's collider has been rebuilt for the new course.
+fn start_hole(
+    mut current: ResMut<CurrentCourse>,
+    mut ev_new_hole: EventWriter<NewHoleEvent>,
+    ground_query: Query<Option<&ColliderTask>, With<Ground>>,
+) {
+    let building = ground_query.iter().any(|task| task.is_some());
+    match current.hole {
+        HoleState::WaitingForGround if building => current.hole = HoleState::BuildingGround,
+        HoleState::BuildingGround if !building => {
+            current.hole = HoleState::Started;
+            if let Some(tee) = current.tee {
+                ev_new_hole.send(NewHoleEvent(tee));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Loads every tile pack. Without them the registry stays empty and nothing is built, but the game carries on.
+fn load_tile_packs(asset_server: Res<AssetServer>, mut packs: ResMut<TilePacks>) {
+    match asset_server.load_folder(TILE_PACK_FOLDER) {
+        Ok(handles) => packs.0 = handles,
+        Err(err) => error!(
+            "Failed to load the tile packs from {}: {}",
+            TILE_PACK_FOLDER, err
+        ),
+    }
 }
 
 fn add_ground(
-    asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
 ) {
-    let tile_defs: Handle<TileDefinitions> = asset_server.load("tiles.ron");
-
     commands
         .spawn_bundle(PbrBundle {
             mesh: meshes.add(Mesh::from(shape::Torus {
                 ..Default::default()
             })),
             material: materials.add(StandardMaterial {
-                base_color: Color::hex("1A7525").unwrap(),
+                base_color: Color::hex(GROUND_COLOUR).unwrap(),
                 // base_color_texture: Some(asset_server.load("textures/checker-grass.png")),
                 ..default()
             }),
@@ -112,24 +240,20 @@ fn add_ground(
         })
         .insert(Collider::cuboid(0.5, 0.5, 0.5))
         .insert(DynamicMesh::new())
-        .insert(Ground)
-        .insert(tile_defs.clone());
+        .insert(Ground);
 }
 fn add_walls(
-    asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
 ) {
-    let tile_defs: Handle<TileDefinitions> = asset_server.load("tiles.ron");
-
     commands
         .spawn_bundle(PbrBundle {
             mesh: meshes.add(Mesh::from(shape::Torus {
                 ..Default::default()
             })),
             material: materials.add(StandardMaterial {
-                base_color: Color::hex("E8B792").unwrap(),
+                base_color: Color::hex(WALL_COLOUR).unwrap(),
                 // base_color_texture: Some(asset_server.load("textures/checker-wood.png")),
                 ..default()
             }),
@@ -137,150 +261,277 @@ fn add_walls(
         })
         .insert(Collider::cuboid(0.5, 0.01, 0.5))
         .insert(DynamicMesh::new())
-        .insert(Wall)
-        .insert(tile_defs.clone());
+        .insert(Wall);
 }
 
 // TODO: Track added/changed tiles instead of firing events.
 /// Updates the ground's dynamic mesh.
 fn update_ground(
     mut ev_update_ground: EventReader<UpdateGroundEvent>,
-    mut ground_query: Query<(&mut DynamicMesh, &Handle<TileDefinitions>), With<Ground>>,
+    mut ground_query: Query<&mut DynamicMesh, With<Ground>>,
     tile_query: Query<&Tile>,
-    defs_asset: Res<Assets<TileDefinitions>>,
+    defs: Res<TileRegistry>,
 ) {
     for _ in ev_update_ground.iter() {
-        for (mut dynamic_mesh, tile_defs) in ground_query.iter_mut() {
-            // Get the tile definitions.
-            let defs = match defs_asset.get(tile_defs) {
-                Some(defs) => &defs.0, // The defs asset exists.
-                // TODO: Figure out how to wait until the asset has loaded (defer the event maybe?)
-                None => continue, // The defs asset does not exist. Just continue.
-            };
-
-            // Clear our dynamic mesh.
-            dynamic_mesh.clear();
-
-            // TODO: Move this into it's own function.
-            // Go over each tile in the world and add them to the dynamic_mesh.
-            for tile in tile_query.iter() {
-                // If the tile definition for this tile exists, add it's triangles to the mesh.
-                if let Some(def) = defs.iter().find(|x| x.id == tile.tile_type) {
-                    for triangle in def.triangles().unwrap_or_default() {
-                        let mut positions: [Vec3; 3] = [Vec3::ZERO; 3];
-
-                        // Rotate each index. Also, while we're iterating add the position.
-                        for i in 0..3 {
-                            let vert = TILE_VERTS
-                                [tile::rotate_index(triangle[i], &tile.rotation) as usize];
-                            positions[i] = (vert + tile.position.as_vec3()) * TILE_BOUNDS;
-                        }
-
-                        // Add our 3 positions to make a triangle.
-                        dynamic_mesh.insert_tri(positions);
-                    }
-                }
-            }
+        // Wait for the tile packs, the registry sends another event once they've loaded.
+        if defs.defs.is_empty() {
+            continue;
+        }
+
+        for mut dynamic_mesh in ground_query.iter_mut() {
+            insert_ground(&mut dynamic_mesh, tile_query.iter(), &defs);
         }
     }
 }
 
 fn update_walls(
     mut ev_update_ground: EventReader<UpdateGroundEvent>,
-    mut wall_query: Query<(&mut DynamicMesh, &Handle<TileDefinitions>), With<Wall>>,
+    mut wall_query: Query<&mut DynamicMesh, With<Wall>>,
     tile_query: Query<&Tile>,
-    defs_asset: Res<Assets<TileDefinitions>>,
+    defs: Res<TileRegistry>,
 ) {
     for _ in ev_update_ground.iter() {
-        for (mut dynamic_mesh, tile_defs) in wall_query.iter_mut() {
-            // Get the tile definitions.
-            let defs = match defs_asset.get(tile_defs) {
-                Some(defs) => &defs.0, // The defs asset exists.
-                // TODO: Figure out how to wait until the asset has loaded (defer the event maybe?)
-                None => continue, // The defs asset does not exist. Just continue.
-            };
-
-            let mut edges = Vec::new();
-            let mut edges_count = Vec::new();
-
-            // Go over each edge in each tile and add them to the list of edges.
-            for tile in tile_query.iter() {
-                if let Some(def) = defs.iter().find(|x| x.id == tile.tile_type) {
-                    for edge_index in def.edges().unwrap_or_default() {
-                        // Rotate each edge, while we're iterating add the position;
-                        let new_edge = Edge(
-                            (TILE_VERTS[rotate_index(edge_index[0], &tile.rotation) as usize]
-                                + tile.position.as_vec3())
-                                * TILE_BOUNDS,
-                            (TILE_VERTS[rotate_index(edge_index[1], &tile.rotation) as usize]
-                                + tile.position.as_vec3())
-                                * TILE_BOUNDS,
-                        );
-
-                        match edges.iter().position(|x| new_edge.eq(x)) {
-                            // If this edge already exists, increment our edge counter for this edge.
-                            Some(index) => {
-                                edges_count[index] = edges_count[index] + 1;
-                            }
-                            // Else add a new edge counter entry and the new edge.
-                            None => {
-                                // Add our edge.
-                                edges.push(new_edge);
-                                edges_count.push(1);
-                            }
-                        }
+        for mut dynamic_mesh in wall_query.iter_mut() {
+            insert_walls(&mut dynamic_mesh, tile_query.iter(), &defs);
+        }
+    }
+}
+
+/// Replaces a dynamic mesh's triangles with the ground of every tile.
+fn insert_ground<'a>(
+    dynamic_mesh: &mut DynamicMesh,
+    tiles: impl IntoIterator<Item = &'a Tile>,
+    defs: &TileDefinitions,
+) {
+    // Clear our dynamic mesh.
+    dynamic_mesh.clear();
+
+    // Go over each tile in the world and add them to the dynamic_mesh.
+    for tile in tiles {
+        // If the tile definition for this tile exists, add it's triangles to the mesh.
+        match defs.get(&tile.tile_type) {
+            Ok(def) => insert_tile_ground(dynamic_mesh, tile, def),
+            Err(err) => error!("{} at {}", err, tile.position),
+        }
+    }
+}
+
+/// Replaces a dynamic mesh's triangles with walls along the edges that only belong to one tile.
+fn insert_walls<'a>(
+    dynamic_mesh: &mut DynamicMesh,
+    tiles: impl IntoIterator<Item = &'a Tile>,
+    defs: &TileDefinitions,
+) {
+    let edges = wall_edges(tiles, defs);
+
+    // Clear our dynamic mesh.
+    dynamic_mesh.clear();
+
+    for edge in edges.iter() {
+        insert_wall(dynamic_mesh, edge);
+    }
+    debug!("{:?} Edges total", edges.len());
+}
+
+/// How thick and how tall the walls are.
+const WALL_SIZE: f32 = 0.08;
+
+/// Gets the edges that only belong to one tile, these are the edges walls are built on.
+pub fn wall_edges<'a>(
+    tiles: impl IntoIterator<Item = &'a Tile>,
+    defs: &TileDefinitions,
+) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    let mut edges_count = Vec::new();
+
+    // Go over each edge in each tile and add them to the list of edges.
+    for tile in tiles {
+        if let Ok(def) = defs.get(&tile.tile_type) {
+            for new_edge in tile_edges(tile, def) {
+                match edges.iter().position(|x| new_edge.eq(x)) {
+                    // If this edge already exists, increment our edge counter for this edge.
+                    Some(index) => {
+                        edges_count[index] = edges_count[index] + 1;
+                    }
+                    // Else add a new edge counter entry and the new edge.
+                    None => {
+                        // Add our edge.
+                        edges.push(new_edge);
+                        edges_count.push(1);
                     }
                 }
             }
+        }
+    }
 
-            // Clear our dynamic mesh.
-            dynamic_mesh.clear();
-
-            let mut total_edges = 0;
-            for i in 0..edges.len() {
-                let edge = &edges[i];
-                let a = edge.0;
-                let b = edge.1;
-                const WALL_SIZE: f32 = 0.08;
-
-                let up = Vec3::Y * WALL_SIZE;
-                let btm = Vec3::Y * -TILE_BOUNDS.y;
-                let inside = (a - b).normalize().cross(Vec3::Y) * WALL_SIZE;
-
-                // If there's one edge (no duplicated edges) then place our edge.
-                if edges_count[i] == 1 {
-                    total_edges += 1;
-                    // Outside wall
-                    dynamic_mesh.insert_tri([b + up, a + up, a + btm]);
-                    dynamic_mesh.insert_tri([a + btm, b + btm, b + up]);
-
-                    // Top face
-                    dynamic_mesh.insert_tri([b + up + inside, a + up, b + up]);
-                    dynamic_mesh.insert_tri([b + up + inside, a + up + inside, a + up]);
-
-                    // Inside face
-                    dynamic_mesh.insert_tri([b + up + inside, b + btm + inside, a + btm + inside]);
-                    dynamic_mesh.insert_tri([b + up + inside, a + btm + inside, a + up + inside]);
-                }
+    // If there's one edge (no duplicated edges) then it gets a wall.
+    edges
+        .into_iter()
+        .zip(edges_count)
+        .filter(|(_, count)| *count == 1)
+        .map(|(edge, _)| edge)
+        .collect()
+}
+
+/// Adds the triangles of a wall along an edge to a dynamic mesh.
+fn insert_wall(dynamic_mesh: &mut DynamicMesh, edge: &Edge) {
+    let a = edge.0;
+    let b = edge.1;
+
+    let up = Vec3::Y * WALL_SIZE;
+    let btm = Vec3::Y * -TILE_BOUNDS.y;
+    let inside = (a - b).normalize().cross(Vec3::Y) * WALL_SIZE;
+
+    // Outside wall
+    dynamic_mesh.insert_tri([b + up, a + up, a + btm]);
+    dynamic_mesh.insert_tri([a + btm, b + btm, b + up]);
+
+    // Top face
+    dynamic_mesh.insert_tri([b + up + inside, a + up, b + up]);
+    dynamic_mesh.insert_tri([b + up + inside, a + up + inside, a + up]);
+
+    // Inside face
+    dynamic_mesh.insert_tri([b + up + inside, b + btm + inside, a + btm + inside]);
+    dynamic_mesh.insert_tri([b + up + inside, a + btm + inside, a + up + inside]);
+}
+
+/// Adds a tile's triangles to a dynamic mesh.
+/// Tiles with a mesh are drawn with it instead of their perimeter and
+/// tiles with a collider only collide with it.
+fn insert_tile_ground(dynamic_mesh: &mut DynamicMesh, tile: &Tile, def: &TileDefinition) {
+    if def.hazard {
+        return;
+    }
+
+    let triangles: Vec<[Vec3; 3]> = match def.mesh {
+        Some(_) => def
+            .mesh_triangles
+            .iter()
+            .map(|triangle| tile.mesh_triangle(*triangle))
+            .collect(),
+        None => def
+            .triangles()
+            .unwrap_or_default()
+            .iter()
+            .map(|triangle| tile.lattice_triangle(*triangle))
+            .collect(),
+    };
+
+    match &def.collider_triangles {
+        Some(collider) => {
+            for triangle in triangles {
+                dynamic_mesh.insert_visual_tri(triangle);
+            }
+            for triangle in collider {
+                dynamic_mesh.insert_collision_tri(tile.mesh_triangle(*triangle));
             }
-            println!("{:?} Edges total", total_edges);
         }
+        None => {
+            for triangle in triangles {
+                dynamic_mesh.insert_tri(triangle);
+            }
+        }
+    }
+}
+
+/// How far from the centre of the cup a ball can be and still drop in.
+const CUP_RADIUS: f32 = 0.095;
+
+/// Whether a ball has dropped into the cup.
+pub fn in_cup(ball: Vec3, cup: Vec3) -> bool {
+    ball.y < cup.y && Vec2::new(ball.x - cup.x, ball.z - cup.z).length() < CUP_RADIUS
+}
+
+/// Finds the cup, the centre of the top of the first tile that has one.
+pub fn find_cup<'a>(
+    tiles: impl IntoIterator<Item = &'a Tile>,
+    defs: &TileDefinitions,
+) -> Option<Vec3> {
+    tiles
+        .into_iter()
+        .find(|tile| defs.get(&tile.tile_type).map_or(false, |def| def.cup))
+        .map(|tile| tile.position.as_vec3() * TILE_BOUNDS)
+}
+
+/// Gets the edges walls are built on for a tile, in world space.
+/// The perimeter is used when there is one, otherwise it's the boundary of the tile's mesh.
+pub fn tile_edges(tile: &Tile, def: &TileDefinition) -> Vec<Edge> {
+    match def.edges() {
+        Some(edges) => edges.iter().map(|edge| tile.lattice_edge(*edge)).collect(),
+        None => def
+            .mesh_edges
+            .iter()
+            .map(|edge| tile.mesh_edge(*edge))
+            .collect(),
     }
 }
 
-fn reload_tile_defs(
+/// Merges the tile packs into the TileRegistry whenever one of them loads, changes or goes away.
+fn rebuild_tile_registry(
     mut ev_update_ground: EventWriter<UpdateGroundEvent>,
     mut ev_assets: EventReader<AssetEvent<TileDefinitions>>,
+    mut registry: ResMut<TileRegistry>,
+    packs: Res<TilePacks>,
+    defs_asset: Res<Assets<TileDefinitions>>,
+    asset_server: Res<AssetServer>,
 ) {
-    for ev in ev_assets.iter() {
-        match ev {
-            AssetEvent::Created { handle: _ } => {}
-            AssetEvent::Modified { handle: _ } => {
-                ev_update_ground.send_default();
-            }
-            AssetEvent::Removed { handle: _ } => {}
+    // Only rebuild once, no matter how many packs changed.
+    if ev_assets.iter().count() == 0 {
+        return;
+    }
+
+    let mut loaded: Vec<(String, &TileDefinitions)> = packs
+        .0
+        .iter()
+        .filter_map(|handle| {
+            let defs = defs_asset.get(handle)?;
+            let path = asset_server.get_handle_path(handle)?;
+            Some((path.path().to_string_lossy().replace('\\', "/"), defs))
+        })
+        .collect();
+
+    sort_tile_packs(&mut loaded);
+
+    let (defs, conflicts) =
+        TileDefinitions::merge(loaded.iter().map(|(path, defs)| (path.as_str(), *defs)));
+    for conflict in conflicts {
+        warn!("{}", conflict);
+    }
+
+    registry.0 = defs;
+    ev_update_ground.send_default();
+}
+
+/// Puts the base pack first and sorts the rest by path, so conflicts are always settled the same way.
+fn sort_tile_packs<T>(packs: &mut [(String, T)]) {
+    packs.sort_by(|(a, _), (b, _)| (a != BASE_TILE_PACK, a).cmp(&(b != BASE_TILE_PACK, b)));
+}
+
+/// Reads and merges every tile pack straight from the assets folder,
+/// for tools that run outside of the asset server.
+pub fn load_tile_packs_from(assets: &Path) -> anyhow::Result<(TileDefinitions, Vec<TileConflict>)> {
+    let mut packs = Vec::new();
+    for entry in fs::read_dir(assets.join(TILE_PACK_FOLDER))? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "ron")
+        {
+            let name = format!(
+                "{}/{}",
+                TILE_PACK_FOLDER,
+                path.file_name().unwrap_or_default().to_string_lossy()
+            );
+            let defs = TileDefinitions::load_file(&path, assets)
+                .with_context(|| format!("Failed to load tile pack {}", name))?;
+            packs.push((name, defs));
         }
     }
+
+    sort_tile_packs(&mut packs);
+    Ok(TileDefinitions::merge(
+        packs.iter().map(|(name, defs)| (name.as_str(), defs)),
+    ))
 }
 
 #[derive(Default)]
@@ -0,0 +1,269 @@
+use bevy::{prelude::*, render::camera::Camera3d};
+use bevy_prototype_debug_lines::DebugLines;
+
+use super::{
+    autotile::{AutoTiler, HeightGrid},
+    course::Course,
+    tile::TILE_BOUNDS,
+    CoursePath, CurrentCourse, TileRegistry,
+};
+use crate::camera::{cursor_ndc, Ray};
+use crate::headless::asset_file;
+use crate::input::{Action, ActionState};
+
+/// The most cells the brush reaches out from the one under the cursor.
+const MAX_BRUSH_RADIUS: i32 = 3;
+const BRUSH_COLOUR: Color = Color::YELLOW;
+
+/// Paints the course's ground by raising, lowering, adding and removing cells,
+/// with the AutoTiler picking the slopes around them.
+/// ToggleBrush (F2) toggles the brush, BrushSmaller and BrushBigger ([ and ]) change it's size
+/// and SaveCourse (Ctrl+S) saves the course.
+pub struct TerrainBrushPlugin;
+impl Plugin for TerrainBrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainBrush>();
+        app.add_system(toggle_brush);
+        app.add_system(paint_terrain.after(toggle_brush));
+        app.add_system(save_course);
+    }
+}
+
+#[derive(Default)]
+pub struct TerrainBrush {
+    /// Whether the mouse paints the ground instead of shooting.
+    pub enabled: bool,
+    /// How many cells the brush reaches out from the one under the cursor.
+    pub radius: i32,
+}
+
+/// The brush's view of the course, kept until the course or the tile definitions change.
+struct BrushCache {
+    grid: HeightGrid,
+    auto_tiler: AutoTiler,
+    /// How many of the course's changes are the brush's own, which the grid has already been changed for.
+    own_edits: usize,
+}
+
+/// Gets the cells in a square around a cell.
+fn square(centre: IVec2, radius: i32) -> impl Iterator<Item = IVec2> {
+    (-radius..=radius).flat_map(move |z| (-radius..=radius).map(move |x| centre + IVec2::new(x, z)))
+}
+
+fn toggle_brush(actions: Res<ActionState>, mut brush: ResMut<TerrainBrush>) {
+    if actions.just_pressed(Action::ToggleBrush) {
+        brush.enabled = !brush.enabled;
+        info!(
+            "Terrain brush {}",
+            match brush.enabled {
+                true => "on, left click raises, right click lowers, hold shift to add or remove",
+                false => "off",
+            }
+        );
+    }
+    if !brush.enabled {
+        return;
+    }
+
+    if actions.just_pressed(Action::BrushSmaller) {
+        brush.radius = (brush.radius - 1).max(0);
+    }
+    if actions.just_pressed(Action::BrushBigger) {
+        brush.radius = (brush.radius + 1).min(MAX_BRUSH_RADIUS);
+    }
+}
+
+/// Outlines the cells under the brush, and changes them when the mouse is clicked.
+fn paint_terrain(
+    brush: Res<TerrainBrush>,
+    actions: Res<ActionState>,
+    windows: Res<Windows>,
+    camera_query: Query<(&GlobalTransform, &Camera), With<Camera3d>>,
+    mut current: ResMut<CurrentCourse>,
+    mut courses: ResMut<Assets<Course>>,
+    mut ev_assets: EventReader<AssetEvent<Course>>,
+    defs: Res<TileRegistry>,
+    mut lines: ResMut<DebugLines>,
+    mut cache: Local<Option<BrushCache>>,
+) {
+    let mut course_changed = false;
+    for ev in ev_assets.iter() {
+        match ev {
+            AssetEvent::Modified { handle } if *handle == current.handle => match cache.as_mut() {
+                Some(cache) if cache.own_edits > 0 => cache.own_edits -= 1,
+                _ => course_changed = true,
+            },
+            AssetEvent::Created { handle } if *handle == current.handle => course_changed = true,
+            _ => {}
+        }
+    }
+    if course_changed || defs.is_changed() {
+        *cache = None;
+    }
+
+    if !brush.enabled {
+        return;
+    }
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let (cam_transform, cam) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    let ray = match Ray::from_screenspace(cursor_ndc(window), cam, cam_transform) {
+        Some(ray) => ray,
+        None => return,
+    };
+    if cache.is_none() {
+        let course = match courses.get(&current.handle) {
+            Some(course) => course,
+            None => return,
+        };
+        *cache = Some(BrushCache {
+            grid: HeightGrid::from_tiles(course.tiles.iter(), &defs),
+            auto_tiler: AutoTiler::new(&defs),
+            own_edits: 0,
+        });
+    }
+    let BrushCache {
+        grid,
+        auto_tiler,
+        own_edits,
+    } = cache.as_mut().unwrap();
+
+    let (centre, level) = match hovered_cell(&ray, grid) {
+        Some(cell) => cell,
+        None => return,
+    };
+    for cell in square(centre, brush.radius) {
+        let height = grid.heights.get(&cell).copied().unwrap_or(level);
+        outline_cell(&mut lines, cell, height);
+    }
+
+    let add_or_remove = actions.pressed(Action::BrushAddOrRemove);
+    let raise = actions.just_pressed(Action::BrushRaise);
+    let lower = actions.just_pressed(Action::BrushLower);
+    if !raise && !lower {
+        return;
+    }
+
+    // The tee's tile is left alone so the balls always have somewhere to start.
+    let tee = current
+        .tee
+        .map(|tee| IVec2::new(tee.position.x, tee.position.z));
+    for cell in square(centre, brush.radius) {
+        if grid.fixed.contains_key(&cell) || Some(cell) == tee {
+            continue;
+        }
+        match (add_or_remove, raise) {
+            (false, true) => {
+                if let Some(height) = grid.heights.get_mut(&cell) {
+                    *height += 1;
+                }
+            }
+            (false, false) => {
+                if let Some(height) = grid.heights.get_mut(&cell) {
+                    *height -= 1;
+                }
+            }
+            (true, true) => {
+                grid.heights.entry(cell).or_insert(level);
+            }
+            (true, false) => {
+                grid.heights.remove(&cell);
+            }
+        }
+    }
+
+    // Changing a cell changes the slopes of the cells around it too.
+    let changed: Vec<IVec2> = square(centre, brush.radius + 1)
+        .filter(|cell| !grid.fixed.contains_key(cell) && Some(*cell) != tee)
+        .collect();
+
+    // Changing the course asset respawns it's tiles, but the hole carries on.
+    // The grid already has the change, so it's kept when the course says it's been modified.
+    let course = match courses.get_mut(&current.handle) {
+        Some(course) => course,
+        None => return,
+    };
+    current.edited = true;
+    *own_edits += 1;
+    course
+        .tiles
+        .retain(|tile| !changed.contains(&IVec2::new(tile.position.x, tile.position.z)));
+    for cell in changed {
+        if let Some(tile) = auto_tiler.tile_cell(&grid.heights, cell) {
+            course.tiles.push(tile);
+        }
+    }
+}
+
+/// Saves the course being painted over it's file with SaveCourse (Ctrl+S).
+fn save_course(
+    brush: Res<TerrainBrush>,
+    actions: Res<ActionState>,
+    path: Res<CoursePath>,
+    current: Res<CurrentCourse>,
+    courses: Res<Assets<Course>>,
+) {
+    if !brush.enabled || !actions.just_pressed(Action::SaveCourse) {
+        return;
+    }
+
+    let course = match courses.get(&current.handle) {
+        Some(course) => course,
+        None => return,
+    };
+    let file = asset_file(&path.0);
+    match course.save_file(&file) {
+        Ok(()) => info!("Saved the course to {}", file.display()),
+        Err(err) => error!("Failed to save the course: {}", err),
+    }
+}
+
+/// Finds the highest cell under the cursor, and it's height.
+/// Empty cells under the cursor are at ground level, and there's no cell when the cursor is above the horizon.
+fn hovered_cell(ray: &Ray, grid: &HeightGrid) -> Option<(IVec2, i32)> {
+    let cell_at = |point: Vec3| IVec2::new(point.x.round() as i32, point.z.round() as i32);
+
+    let mut levels: Vec<i32> = grid.heights.values().copied().collect();
+    levels.sort_unstable();
+    levels.dedup();
+    for level in levels.into_iter().rev() {
+        let point = ray.intersect_plane(Vec3::Y, Vec3::Y * level as f32 * TILE_BOUNDS.y);
+        if let Some(cell) = point.map(cell_at) {
+            if grid.heights.get(&cell) == Some(&level) {
+                return Some((cell, level));
+            }
+        }
+    }
+
+    let point = ray.intersect_plane(Vec3::Y, Vec3::ZERO)?;
+    Some((cell_at(point), 0))
+}
+
+fn outline_cell(lines: &mut DebugLines, cell: IVec2, height: i32) {
+    // Lift the outline off the ground so it isn't hidden by it.
+    let centre = Vec3::new(
+        cell.x as f32,
+        height as f32 * TILE_BOUNDS.y + 0.01,
+        cell.y as f32,
+    );
+    let corners = [
+        Vec3::new(0.5, 0.0, -0.5),
+        Vec3::new(0.5, 0.0, 0.5),
+        Vec3::new(-0.5, 0.0, 0.5),
+        Vec3::new(-0.5, 0.0, -0.5),
+    ];
+    for i in 0..corners.len() {
+        let next = (i + 1) % corners.len();
+        lines.line_colored(
+            centre + corners[i],
+            centre + corners[next],
+            0.0,
+            BRUSH_COLOUR,
+        );
+    }
+}
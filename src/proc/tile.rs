@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use bevy::{math::const_vec3, prelude::*, reflect::TypeUuid};
 
@@ -14,7 +14,7 @@ pub const TILE_VERTS: &[Vec3] = &[
     const_vec3!([-0.5, 0.0, -0.5]),  // 7
 ];
 
-#[derive(Copy, Clone, Debug, Deserialize, TypeUuid)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, TypeUuid)]
 #[uuid = "879067aa-3b4d-4144-aed2-a6f9ab701655"]
 pub enum Orientation {
     North = 0,
@@ -23,6 +23,59 @@ pub enum Orientation {
     West = 3,
 }
 
+impl Orientation {
+    /// The rotation around the Y axis that matches `rotate_index`.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2 * *self as u8 as f32)
+    }
+}
+
+/// Reflections applied to a tile before it's rotated by its Orientation.
+/// The lattice is half as tall as it is wide, so the only tip that keeps a tile's points on it
+/// is a half turn, which puts the ground underneath. Tiles can't be tipped for that reason.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Flip {
+    None,
+    /// Mirrored along the X axis.
+    Mirror,
+}
+
+impl Default for Flip {
+    fn default() -> Self {
+        Flip::None
+    }
+}
+
+impl Flip {
+    pub fn is_none(&self) -> bool {
+        *self == Flip::None
+    }
+
+    pub fn mirrored(&self) -> bool {
+        *self == Flip::Mirror
+    }
+
+    /// Gets the index of the TILE_VERTS point that this point lands on when flipped.
+    pub fn flip_index(&self, index: u8) -> u8 {
+        // Mirroring swaps the +X and -X corners.
+        const MIRROR: [u8; 8] = [3, 2, 1, 0, 7, 6, 5, 4];
+
+        match self.mirrored() {
+            true => MIRROR[index as usize],
+            false => index,
+        }
+    }
+
+    /// Flips a point in a tile mesh's space, where the origin is the centre of the tile's top face.
+    pub fn flip_point(&self, point: Vec3) -> Vec3 {
+        let mut point = point;
+        if self.mirrored() {
+            point.x = -point.x;
+        }
+        point
+    }
+}
+
 pub fn rotate_index(index: u8, orientation: &Orientation) -> u8 {
     let rotations = *orientation as u8;
     if index >= 4 {
@@ -31,12 +84,66 @@ pub fn rotate_index(index: u8, orientation: &Orientation) -> u8 {
         (rotations + index) % 4
     }
 }
-#[derive(Debug, Deserialize, TypeUuid, Component)]
+#[derive(Debug, Clone, Deserialize, Serialize, TypeUuid, Component)]
 #[uuid = "aa5fc0fb-722d-4d8f-b0cd-9526f1a0e75e"]
 pub struct Tile {
     pub position: IVec3,
     pub rotation: Orientation,
-    pub tile_type: u8,
+    #[serde(default, skip_serializing_if = "Flip::is_none")]
+    pub flip: Flip,
+    /// The key of this tile's TileDefinition.
+    pub tile_type: String,
+}
+
+impl Tile {
+    /// Gets the world position of one of the TILE_VERTS after flipping, rotating and moving it.
+    pub fn lattice_point(&self, index: u8) -> Vec3 {
+        let index = rotate_index(self.flip.flip_index(index), &self.rotation);
+        (TILE_VERTS[index as usize] + self.position.as_vec3()) * TILE_BOUNDS
+    }
+
+    /// Moves a point from a tile mesh's space into the world.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation.rotation() * self.flip.flip_point(point)
+            + self.position.as_vec3() * TILE_BOUNDS
+    }
+
+    /// Puts a triangle of TILE_VERTS indices into the world.
+    pub fn lattice_triangle(&self, triangle: [u8; 3]) -> [Vec3; 3] {
+        self.wind_triangle(triangle.map(|index| self.lattice_point(index)))
+    }
+
+    /// Puts a triangle from a tile mesh into the world.
+    pub fn mesh_triangle(&self, triangle: [Vec3; 3]) -> [Vec3; 3] {
+        self.wind_triangle(triangle.map(|point| self.transform_point(point)))
+    }
+
+    /// Puts an edge of TILE_VERTS indices into the world.
+    pub fn lattice_edge(&self, edge: [u8; 2]) -> Edge {
+        self.wind_edge(edge.map(|index| self.lattice_point(index)))
+    }
+
+    /// Puts an edge from a tile mesh into the world.
+    pub fn mesh_edge(&self, edge: [Vec3; 2]) -> Edge {
+        self.wind_edge(edge.map(|point| self.transform_point(point)))
+    }
+
+    /// Mirroring turns our triangles inside out, so swap two of the points to keep them facing out.
+    fn wind_triangle(&self, triangle: [Vec3; 3]) -> [Vec3; 3] {
+        match self.flip.mirrored() {
+            true => [triangle[0], triangle[2], triangle[1]],
+            false => triangle,
+        }
+    }
+
+    /// Walls are built on the left of an edge when looking down, so keep the edges going
+    /// around the tile the same way after a mirror.
+    fn wind_edge(&self, edge: [Vec3; 2]) -> Edge {
+        match self.flip.mirrored() {
+            true => Edge(edge[1], edge[0]),
+            false => Edge(edge[0], edge[1]),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -1,19 +1,51 @@
-use std::vec;
+use std::{fmt, fs, path::Path, vec};
 
-use serde::Deserialize;
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
 
 use bevy::{
     asset::{AssetLoader, LoadedAsset},
+    math::Vec3,
     reflect::TypeUuid,
-    utils::BoxedFuture,
+    utils::{BoxedFuture, HashMap},
 };
 
-#[derive(Debug, Deserialize, TypeUuid)]
+use super::{mesh_asset, migrate::read_tile_pack};
+
+#[derive(Debug, Clone, Deserialize, Serialize, TypeUuid)]
 #[uuid = "b43e6937-97e2-4fb9-9146-16f894bf814d"]
 pub struct TileDefinition {
-    pub id: u8,
+    /// The name tiles refer to this definition by, namespaced like "base:ramp".
+    pub key: String,
     pub perimeter: Vec<u8>,
     pub name: String,
+    /// An .obj or .glb mesh (relative to the assets folder) drawn instead of the perimeter.
+    /// The mesh's origin sits on the centre of the tile's top face.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<String>,
+    /// An .obj or .glb mesh used for collisions instead of the drawn triangles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collider: Option<String>,
+    /// Whether the tile has the cup at the centre of it's top face.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub cup: bool,
+    /// Whether the tile is a gap in the ground, balls that roll onto it fall off the course.
+    /// It's edges still join up with the tiles around it, so there's no walls in the way.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hazard: bool,
+    /// The triangles loaded from `mesh`.
+    #[serde(skip)]
+    pub mesh_triangles: Vec<[Vec3; 3]>,
+    /// The edges of `mesh_triangles` that walls are built on when there's no perimeter.
+    #[serde(skip)]
+    pub mesh_edges: Vec<[Vec3; 2]>,
+    /// The triangles loaded from `collider`.
+    #[serde(skip)]
+    pub collider_triangles: Option<Vec<[Vec3; 3]>>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 impl TileDefinition {
@@ -50,21 +82,145 @@ impl TileDefinition {
 
         Some(edges)
     }
+
+    /// Sets the mesh triangles, and the edges walls are built on, from the bytes of `mesh`.
+    pub fn load_mesh(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        self.mesh_triangles = mesh_asset::load_triangles(path, bytes)?;
+        self.mesh_edges = mesh_asset::boundary_edges(&self.mesh_triangles);
+        Ok(())
+    }
+
+    /// Sets the collider triangles from the bytes of `collider`.
+    pub fn load_collider(&mut self, path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        self.collider_triangles = Some(mesh_asset::load_triangles(path, bytes)?);
+        Ok(())
+    }
 }
 
 impl Default for TileDefinition {
     fn default() -> Self {
         Self {
-            id: 0,
+            key: String::new(),
             name: String::from("Error: Unknown"),
             perimeter: Default::default(),
+            mesh: None,
+            collider: None,
+            cup: false,
+            hazard: false,
+            mesh_triangles: Default::default(),
+            mesh_edges: Default::default(),
+            collider_triangles: None,
         }
     }
 }
 
-#[derive(Default, Debug, Deserialize, TypeUuid)]
+#[derive(Default, Debug, TypeUuid)]
 #[uuid = "74e0d658-5507-4195-9222-dff94b6839f3"]
-pub struct TileDefinitions(pub Vec<TileDefinition>);
+pub struct TileDefinitions {
+    pub defs: Vec<TileDefinition>,
+    /// The index of each definition in `defs` by it's key.
+    lookup: HashMap<String, usize>,
+}
+
+impl TileDefinitions {
+    /// Creates our lookup table, failing when a key is used by more than one definition.
+    pub fn new(defs: Vec<TileDefinition>) -> anyhow::Result<Self> {
+        let mut lookup = HashMap::default();
+        for (index, def) in defs.iter().enumerate() {
+            if lookup.insert(def.key.clone(), index).is_some() {
+                bail!("Tile type \"{}\" is defined more than once", def.key);
+            }
+        }
+        Ok(Self { defs, lookup })
+    }
+
+    /// Reads a tile pack straight from a file, for tools that run outside of the asset server.
+    /// Mesh and collider paths are relative to the assets folder.
+    pub fn load_file(path: impl AsRef<Path>, assets: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut array = read_tile_pack(&fs::read(path)?)?.value;
+
+        for def in array.iter_mut() {
+            if let Some(mesh) = def.mesh.clone() {
+                def.load_mesh(&mesh, &fs::read(assets.as_ref().join(&mesh))?)?;
+            }
+            if let Some(collider) = def.collider.clone() {
+                def.load_collider(&collider, &fs::read(assets.as_ref().join(&collider))?)?;
+            }
+        }
+
+        Self::new(array)
+    }
+
+    /// Merges tile packs into one set of definitions.
+    /// When packs share a key the definition from the earlier pack is kept.
+    pub fn merge<'a>(
+        packs: impl IntoIterator<Item = (&'a str, &'a TileDefinitions)>,
+    ) -> (Self, Vec<TileConflict>) {
+        let mut defs = Vec::new();
+        let mut lookup: HashMap<String, usize> = HashMap::default();
+        let mut sources: Vec<String> = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for (pack, pack_defs) in packs {
+            for def in pack_defs.defs.iter() {
+                match lookup.get(&def.key) {
+                    Some(index) => conflicts.push(TileConflict {
+                        key: def.key.clone(),
+                        kept: sources[*index].clone(),
+                        ignored: pack.to_string(),
+                    }),
+                    None => {
+                        lookup.insert(def.key.clone(), defs.len());
+                        defs.push(def.clone());
+                        sources.push(pack.to_string());
+                    }
+                }
+            }
+        }
+
+        (Self { defs, lookup }, conflicts)
+    }
+
+    /// Gets the definition for a tile type.
+    pub fn get(&self, key: &str) -> Result<&TileDefinition, UnknownTileType> {
+        match self.lookup.get(key) {
+            Some(index) => Ok(&self.defs[*index]),
+            None => Err(UnknownTileType(key.to_string())),
+        }
+    }
+}
+
+/// A tile refers to a tile type that has no definition.
+#[derive(Debug)]
+pub struct UnknownTileType(pub String);
+
+impl fmt::Display for UnknownTileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown tile type \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTileType {}
+
+/// Two tile packs define the same tile type.
+#[derive(Debug)]
+pub struct TileConflict {
+    pub key: String,
+    /// The pack whose definition is used.
+    pub kept: String,
+    /// The pack whose definition is ignored.
+    pub ignored: String,
+}
+
+impl fmt::Display for TileConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tile type \"{}\" in {} is already defined in {}, ignoring it",
+            self.key, self.ignored, self.kept
+        )
+    }
+}
 
 #[derive(Default)]
 pub struct TileDefinitionsLoader;
@@ -76,8 +232,21 @@ impl AssetLoader for TileDefinitionsLoader {
         load_context: &'a mut bevy::asset::LoadContext,
     ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
         Box::pin(async move {
-            let array = ron::de::from_bytes::<Vec<TileDefinition>>(bytes)?;
-            let asset = TileDefinitions(array);
+            let mut array = read_tile_pack(bytes)?.value;
+
+            // Load the meshes our definitions refer to.
+            for def in array.iter_mut() {
+                if let Some(path) = def.mesh.clone() {
+                    let bytes = load_context.read_asset_bytes(&path).await?;
+                    def.load_mesh(&path, &bytes)?;
+                }
+                if let Some(path) = def.collider.clone() {
+                    let bytes = load_context.read_asset_bytes(&path).await?;
+                    def.load_collider(&path, &bytes)?;
+                }
+            }
+
+            let asset = TileDefinitions::new(array)?;
             load_context.set_default_asset(LoadedAsset::new(asset));
             Ok(())
         })
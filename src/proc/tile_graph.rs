@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use super::{
+    tile::{Tile, TILE_BOUNDS},
+    tile_definitions::TileDefinitions,
+    tile_edges,
+};
+
+/// Which tiles a ball can roll between.
+/// Tiles are connected when they share an edge, so walls and slopes that don't line up
+/// (like the top of a ramp next to a lower tile) keep them apart.
+pub struct TileGraph {
+    /// The middle of each tile's edges.
+    pub centres: Vec<Vec3>,
+    /// The indices of the tiles each tile is connected to.
+    pub neighbours: Vec<Vec<usize>>,
+}
+
+impl TileGraph {
+    /// Connects tiles, the indices of the graph are the indices of `tiles`.
+    /// Tiles with an unknown type, and hazards, aren't connected to anything.
+    pub fn new(tiles: &[Tile], defs: &TileDefinitions) -> Self {
+        let mut edges = Vec::new();
+        let mut centres = Vec::new();
+        for tile in tiles {
+            let tile_edges = match defs.get(&tile.tile_type) {
+                Ok(def) if !def.hazard => tile_edges(tile, def),
+                _ => Vec::new(),
+            };
+            let centre = match tile_edges.len() {
+                0 => tile.position.as_vec3() * TILE_BOUNDS,
+                count => tile_edges.iter().map(|edge| edge.0).sum::<Vec3>() / count as f32,
+            };
+            edges.push(tile_edges);
+            centres.push(centre);
+        }
+
+        let mut neighbours = vec![Vec::new(); tiles.len()];
+        for a in 0..tiles.len() {
+            for b in a + 1..tiles.len() {
+                if edges[a].iter().any(|edge| edges[b].contains(edge)) {
+                    neighbours[a].push(b);
+                    neighbours[b].push(a);
+                }
+            }
+        }
+
+        Self {
+            centres,
+            neighbours,
+        }
+    }
+
+    /// Gets which tiles can be reached from a tile.
+    pub fn reachable(&self, start: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.centres.len()];
+        let mut open = vec![start];
+        reached[start] = true;
+
+        while let Some(current) = open.pop() {
+            for next in self.neighbours[current].iter() {
+                if !reached[*next] {
+                    reached[*next] = true;
+                    open.push(*next);
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Finds the shortest path between two tiles and it's length, measured between tile centres.
+    pub fn shortest_path(&self, start: usize, goal: usize) -> Option<(Vec<usize>, f32)> {
+        let count = self.centres.len();
+        let mut distances = vec![f32::INFINITY; count];
+        let mut previous = vec![None; count];
+        let mut visited = vec![false; count];
+        distances[start] = 0.0;
+
+        // Dijkstra's, there's few enough tiles to find the closest by searching.
+        while let Some(current) = (0..count)
+            .filter(|i| !visited[*i] && distances[*i].is_finite())
+            .min_by(|a, b| distances[*a].total_cmp(&distances[*b]))
+        {
+            if current == goal {
+                break;
+            }
+            visited[current] = true;
+
+            for next in self.neighbours[current].iter().copied() {
+                let distance =
+                    distances[current] + self.centres[current].distance(self.centres[next]);
+                if distance < distances[next] {
+                    distances[next] = distance;
+                    previous[next] = Some(current);
+                }
+            }
+        }
+
+        if !distances[goal].is_finite() {
+            return None;
+        }
+
+        let mut path = vec![goal];
+        let mut current = goal;
+        while let Some(prev) = previous[current] {
+            path.push(prev);
+            current = prev;
+        }
+        path.reverse();
+
+        Some((path, distances[goal]))
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{
+    prelude::*,
+    render::{camera::Camera3d, mesh::PrimitiveTopology},
+    utils::HashMap,
+};
+
+use super::{
+    dynamic_mesh::DynamicMesh, insert_wall, tile::*, wall_edges, TileRegistry, UpdateGroundEvent,
+    Wall, WALL_COLOUR, WALL_SIZE,
+};
+use crate::ball::{Ball, BALL_RADIUS};
+
+/// Fades out the walls between the camera and the balls, so the balls can always be seen.
+/// The Wall entity still collides, but it's drawn in chunks of walls that can be faded on their own.
+pub struct WallCutawayPlugin;
+
+impl Plugin for WallCutawayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(add_wall_materials);
+        app.add_system(hide_walls);
+        app.add_system(spawn_wall_segments);
+        app.add_system(cut_away_walls.after(spawn_wall_segments));
+    }
+}
+
+/// How much of a faded wall can still be seen.
+const FADED_ALPHA: f32 = 0.25;
+/// Walls this close to the line between the camera and a ball still fade, so the whole ball can be seen.
+const OCCLUSION_MARGIN: f32 = BALL_RADIUS * 2.0;
+/// How many tiles across each chunk of walls is, bigger chunks are fewer meshes but fade more walls at once.
+const CHUNK_TILES: f32 = 3.0;
+
+/// The materials of solid and faded wall segments.
+struct WallMaterials {
+    solid: Handle<StandardMaterial>,
+    faded: Handle<StandardMaterial>,
+}
+
+/// The walls in a chunk of the course, they fade together when any of them are in front of a ball.
+#[derive(Component)]
+pub struct WallSegment {
+    /// The bottom edges of the walls.
+    pub edges: Vec<Edge>,
+    /// Whether any of the walls are between the camera and a ball.
+    pub occluding: bool,
+}
+
+fn add_wall_materials(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let colour = Color::hex(WALL_COLOUR).unwrap();
+    let mut faded = colour;
+    faded.set_a(FADED_ALPHA);
+    commands.insert_resource(WallMaterials {
+        solid: materials.add(StandardMaterial {
+            base_color: colour,
+            ..default()
+        }),
+        faded: materials.add(StandardMaterial {
+            base_color: faded,
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+    });
+}
+
+/// Takes the Wall's mesh away, so it isn't drawn or built for nothing. The segments are drawn in it's place.
+fn hide_walls(mut commands: Commands, wall_query: Query<Entity, Added<Wall>>) {
+    for ent in wall_query.iter() {
+        commands.entity(ent).remove::<Handle<Mesh>>();
+    }
+}
+
+/// Replaces the wall segments whenever the walls are rebuilt.
+fn spawn_wall_segments(
+    mut commands: Commands,
+    mut ev_update_ground: EventReader<UpdateGroundEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<WallMaterials>,
+    segment_query: Query<Entity, With<WallSegment>>,
+    tile_query: Query<&Tile>,
+    defs: Res<TileRegistry>,
+) {
+    if ev_update_ground.iter().count() == 0 {
+        return;
+    }
+
+    for ent in segment_query.iter() {
+        commands.entity(ent).despawn();
+    }
+
+    // Group the walls by the chunk their middle is in.
+    let mut chunks: HashMap<IVec2, Vec<Edge>> = HashMap::default();
+    for edge in wall_edges(tile_query.iter(), &defs) {
+        let middle = (edge.0 + edge.1) * 0.5 / (TILE_BOUNDS * CHUNK_TILES);
+        let chunk = IVec2::new(middle.x.floor() as i32, middle.z.floor() as i32);
+        chunks.entry(chunk).or_default().push(edge);
+    }
+
+    // The meshes are built in the background like the rest of the dynamic meshes.
+    for (_, edges) in chunks {
+        let mut dynamic_mesh = DynamicMesh::new();
+        for edge in edges.iter() {
+            insert_wall(&mut dynamic_mesh, edge);
+        }
+
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: meshes.add(Mesh::new(PrimitiveTopology::TriangleList)),
+                material: materials.solid.clone(),
+                ..default()
+            })
+            .insert(dynamic_mesh)
+            .insert(WallSegment {
+                edges,
+                occluding: false,
+            });
+    }
+}
+
+/// Fades the chunks of walls that are between the camera and any of the balls, and restores them once they're clear.
+fn cut_away_walls(
+    materials: Res<WallMaterials>,
+    mut segment_query: Query<(&mut WallSegment, &mut Handle<StandardMaterial>)>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+    ball_query: Query<&GlobalTransform, With<Ball>>,
+) {
+    let camera = match camera_query.get_single() {
+        Ok(camera) => camera.translation,
+        Err(_) => return,
+    };
+
+    for (mut segment, mut material) in segment_query.iter_mut() {
+        let occluding = segment.edges.iter().any(|edge| {
+            ball_query
+                .iter()
+                .any(|ball| occludes(edge, camera, ball.translation))
+        });
+
+        // Only touch the chunks that changed, so the rest aren't marked as changed every frame.
+        if segment.occluding != occluding {
+            segment.occluding = occluding;
+            *material = match occluding {
+                true => materials.faded.clone(),
+                false => materials.solid.clone(),
+            };
+        }
+    }
+}
+
+/// Whether a wall along an edge is in the way of the line from the camera to a ball.
+fn occludes(edge: &Edge, camera: Vec3, ball: Vec3) -> bool {
+    let flat = |point: Vec3| Vec2::new(point.x, point.z);
+
+    // Lengthen the wall a little so balls peeking around it's ends are still seen.
+    let along = (flat(edge.1) - flat(edge.0)).normalize_or_zero() * OCCLUSION_MARGIN;
+    let a = flat(edge.0) - along;
+    let b = flat(edge.1) + along;
+
+    // Find where the line crosses the wall from above.
+    let start = flat(camera);
+    let line = flat(ball) - start;
+    let wall = b - a;
+    let denominator = line.perp_dot(wall);
+    if denominator.abs() < f32::EPSILON {
+        return false;
+    }
+    let t = (a - start).perp_dot(wall) / denominator;
+    let u = (a - start).perp_dot(line) / denominator;
+    if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+
+    // The line is in the way if it passes below the top of the wall where it crosses.
+    let height = camera.lerp(ball, t).y;
+    let top = edge.0.lerp(edge.1, u).y + WALL_SIZE;
+    height < top + OCCLUSION_MARGIN
+}
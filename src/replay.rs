@@ -0,0 +1,416 @@
+use std::{fs, path::Path};
+
+use anyhow::bail;
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ball::{ApplyShots, Ball, Player, Players, ShootEvent, MIN_VELOCITY};
+use crate::input::{Action, ActionState};
+use crate::proc::{CoursePath, NewHoleEvent};
+
+/// Where replays are saved with SaveReplay (F5).
+const REPLAY_PATH: &str = "replays/latest.replay.ron";
+/// How far a replayed shot can end up from where it did when it was recorded.
+const REPLAY_TOLERANCE: f32 = 0.001;
+/// Shots that still haven't moved after this many steps are finished.
+const MAX_STILL_STEPS: usize = 10;
+/// The length of a physics step.
+pub const PHYSICS_DT: f32 = 1.0 / 60.0;
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(RecordPlugin);
+        app.add_system(save_replay);
+    }
+}
+
+/// Records the shots taken on each hole, and plays them back when there's a Playback.
+/// Headless simulations use this without the game, so they can record and check replays too.
+pub struct RecordPlugin;
+impl Plugin for RecordPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayClock>();
+        app.init_resource::<Recorder>();
+        app.add_system(play_shots.before(ApplyShots));
+        app.add_system(record_shots.after(ApplyShots));
+        app.add_system_to_stage(CoreStage::PostUpdate, record_paths);
+        app.add_system_to_stage(CoreStage::PostUpdate, tick_clock);
+    }
+}
+
+/// Steps the physics by PHYSICS_DT at a time, so the same shots always play out the same way.
+pub struct FixedPhysicsPlugin;
+impl Plugin for FixedPhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhysicsClock>();
+        app.add_startup_system(deterministic_physics);
+        app.add_system_to_stage(CoreStage::PreUpdate, step_physics);
+    }
+}
+
+/// Decides which frames the physics steps on.
+/// The physics takes at most one step a frame, and only once a whole step of time has passed,
+/// so it runs at the same speed at any frame rate above 60 (and slows down below it instead of jumping).
+#[derive(Default)]
+pub struct PhysicsClock {
+    /// Step every frame whatever the time, for headless simulations that update as fast as they can.
+    pub every_frame: bool,
+    /// Whether the physics steps this frame.
+    pub stepping: bool,
+    /// Whether the physics stepped last frame.
+    pub stepped: bool,
+    /// The time that's passed that hasn't been stepped yet.
+    accumulated: f32,
+}
+
+impl PhysicsClock {
+    pub fn every_frame() -> Self {
+        PhysicsClock {
+            every_frame: true,
+            ..default()
+        }
+    }
+}
+
+/// Every shot taken on a hole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// The path of the course that was played.
+    pub course: String,
+    pub players: usize,
+    pub shots: Vec<ShotRecord>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Replay> {
+        let text = fs::read_to_string(path)?;
+        let replay: Replay = ron::de::from_str(&text)?;
+        if replay.players == 0 {
+            bail!("The replay has no players");
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        if let Some(folder) = path.as_ref().parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// A single shot and where it took the ball.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShotRecord {
+    pub player: usize,
+    /// The aim vector (Ball.0) the shot was fired with.
+    pub aim: Vec3,
+    /// How hard the shot was, from 0 to 1.
+    pub power: f32,
+    /// Seconds since the game started.
+    pub time: f64,
+    /// The physics step since the start of the hole that the shot was fired on.
+    pub step: u64,
+    /// Where the ball was before the shot.
+    pub start: Vec3,
+    /// The ball's translation and rotation after each physics step until it came to rest.
+    pub path: Vec<(Vec3, Quat)>,
+}
+
+impl ShotRecord {
+    /// Gets where the ball came to rest.
+    pub fn end(&self) -> Vec3 {
+        self.path
+            .last()
+            .map(|(position, _)| *position)
+            .unwrap_or(self.start)
+    }
+}
+
+/// Counts the physics steps since the hole started.
+#[derive(Default)]
+pub struct ReplayClock {
+    pub step: u64,
+    pub started: bool,
+}
+
+/// Records the shots taken on the current hole.
+#[derive(Default)]
+pub struct Recorder {
+    pub shots: Vec<ShotRecord>,
+    /// The balls that are still moving from a shot.
+    in_flight: HashMap<Entity, InFlight>,
+}
+
+struct InFlight {
+    /// The index of the shot in `Recorder.shots`.
+    shot: usize,
+    /// Whether the ball has started moving yet.
+    moving: bool,
+}
+
+/// Plays back a replay instead of taking shots from the players.
+pub struct Playback {
+    replay: Replay,
+    /// The next shot to take.
+    next: usize,
+    checked: usize,
+    /// What went wrong with each shot that didn't match the replay.
+    mismatches: Vec<String>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next: 0,
+            checked: 0,
+            mismatches: Vec::new(),
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Whether every shot in the replay has been played back and checked.
+    pub fn finished(&self) -> bool {
+        self.checked >= self.replay.shots.len()
+    }
+
+    /// Fails if any of the shots played back so far didn't match the replay.
+    pub fn result(&self) -> anyhow::Result<()> {
+        if !self.mismatches.is_empty() {
+            bail!(
+                "{} of {} shots didn't match the replay:\n{}",
+                self.mismatches.len(),
+                self.checked,
+                self.mismatches.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// Compares a shot that's been played back with the recorded one.
+    fn check(&mut self, index: usize, shot: &ShotRecord) {
+        let recorded = match self.replay.shots.get(index) {
+            Some(recorded) => recorded,
+            None => return,
+        };
+
+        if shot.start.distance(recorded.start) > REPLAY_TOLERANCE {
+            self.mismatches.push(format!(
+                "Shot {} started at {} but was recorded starting at {}",
+                index, shot.start, recorded.start
+            ));
+        }
+
+        let divergence = shot.end().distance(recorded.end());
+        if divergence > REPLAY_TOLERANCE {
+            self.mismatches.push(format!(
+                "Shot {} diverged from the replay, it ended at {} which is {:.4} from {}",
+                index,
+                shot.end(),
+                divergence,
+                recorded.end()
+            ));
+        }
+
+        self.checked += 1;
+        if self.finished() {
+            match self.result() {
+                Ok(()) => info!("Replay finished, all {} shots matched", self.checked),
+                Err(err) => error!("Replay finished, {}", err),
+            }
+        }
+    }
+}
+
+/// Makes each step of the physics the same length, so the same shots always play out the same way.
+fn deterministic_physics(mut config: ResMut<RapierConfiguration>) {
+    config.timestep_mode = TimestepMode::Fixed {
+        dt: PHYSICS_DT,
+        substeps: 1,
+    };
+}
+
+/// Turns the physics on for the frames it should step on.
+fn step_physics(
+    mut clock: ResMut<PhysicsClock>,
+    mut config: ResMut<RapierConfiguration>,
+    time: Res<Time>,
+) {
+    clock.stepped = clock.stepping;
+    clock.stepping = match clock.every_frame {
+        true => true,
+        false => {
+            clock.accumulated += time.delta_seconds();
+            if clock.accumulated >= PHYSICS_DT {
+                // Time that can't be caught up on in one step is dropped.
+                clock.accumulated = (clock.accumulated - PHYSICS_DT).min(PHYSICS_DT);
+                true
+            } else {
+                false
+            }
+        }
+    };
+    config.physics_pipeline_active = clock.stepping;
+}
+
+/// Takes the replay's shots on the same steps they were recorded on.
+fn play_shots(
+    playback: Option<ResMut<Playback>>,
+    clock: Res<ReplayClock>,
+    balls: Query<(Entity, &Player), With<Ball>>,
+    mut ev_shoot: EventWriter<ShootEvent>,
+) {
+    let mut playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    if !clock.started {
+        return;
+    }
+
+    while let Some((step, player, aim)) = playback
+        .replay
+        .shots
+        .get(playback.next)
+        .map(|shot| (shot.step, shot.player, shot.aim))
+    {
+        if step > clock.step {
+            break;
+        }
+
+        match balls
+            .iter()
+            .find(|(_, ball_player)| ball_player.0 == player)
+        {
+            Some((ball, _)) => ev_shoot.send(ShootEvent { ball, aim }),
+            None => warn!(
+                "There's no ball for player {} to take shot {}",
+                player, playback.next
+            ),
+        }
+        playback.next += 1;
+    }
+}
+
+fn record_shots(
+    mut ev_shoot: EventReader<ShootEvent>,
+    mut recorder: ResMut<Recorder>,
+    clock: Res<ReplayClock>,
+    time: Res<Time>,
+    balls: Query<(&Player, &Transform), With<Ball>>,
+) {
+    for shot in ev_shoot.iter() {
+        let (player, transform) = match balls.get(shot.ball) {
+            Ok(ball) => ball,
+            Err(_) => continue,
+        };
+
+        recorder.shots.push(ShotRecord {
+            player: player.0,
+            aim: shot.aim,
+            power: shot.aim.length(),
+            time: time.seconds_since_startup(),
+            step: clock.step,
+            start: transform.translation,
+            path: Vec::new(),
+        });
+        let index = recorder.shots.len() - 1;
+        recorder.in_flight.insert(
+            shot.ball,
+            InFlight {
+                shot: index,
+                moving: false,
+            },
+        );
+    }
+}
+
+/// Records where each shot takes it's ball until the ball comes to rest.
+fn record_paths(
+    mut recorder: ResMut<Recorder>,
+    mut playback: Option<ResMut<Playback>>,
+    physics: Res<PhysicsClock>,
+    balls: Query<(&Transform, &Velocity), With<Ball>>,
+) {
+    // The path has a point for each step.
+    if !physics.stepping {
+        return;
+    }
+
+    let recorder = &mut *recorder;
+    let mut finished = Vec::new();
+
+    for (ent, flight) in recorder.in_flight.iter_mut() {
+        let (transform, velocity) = match balls.get(*ent) {
+            Ok(ball) => ball,
+            // The ball has gone, there's nothing left to record.
+            Err(_) => {
+                finished.push(*ent);
+                continue;
+            }
+        };
+
+        let shot = &mut recorder.shots[flight.shot];
+        shot.path.push((transform.translation, transform.rotation));
+
+        let resting = velocity.linvel.length_squared() < MIN_VELOCITY;
+        flight.moving |= !resting;
+        if resting && (flight.moving || shot.path.len() > MAX_STILL_STEPS) {
+            finished.push(*ent);
+        }
+    }
+
+    for ent in finished {
+        if let Some(flight) = recorder.in_flight.remove(&ent) {
+            if let Some(playback) = playback.as_mut() {
+                playback.check(flight.shot, &recorder.shots[flight.shot]);
+            }
+        }
+    }
+}
+
+/// Restarts the clock and the recording at the start of each hole.
+fn tick_clock(
+    mut clock: ResMut<ReplayClock>,
+    mut recorder: ResMut<Recorder>,
+    mut ev_new_hole: EventReader<NewHoleEvent>,
+    physics: Res<PhysicsClock>,
+) {
+    if ev_new_hole.iter().count() > 0 {
+        clock.step = 0;
+        clock.started = true;
+        recorder.shots.clear();
+        recorder.in_flight.clear();
+    } else if clock.started && physics.stepping {
+        clock.step += 1;
+    }
+}
+
+fn save_replay(
+    actions: Res<ActionState>,
+    recorder: Res<Recorder>,
+    course: Res<CoursePath>,
+    players: Res<Players>,
+) {
+    if !actions.just_pressed(Action::SaveReplay) {
+        return;
+    }
+
+    let replay = Replay {
+        course: course.0.clone(),
+        players: players.0,
+        shots: recorder.shots.clone(),
+    };
+    match replay.save(REPLAY_PATH) {
+        Ok(()) => info!("Saved {} shots to {}", replay.shots.len(), REPLAY_PATH),
+        Err(err) => error!("Failed to save the replay: {}", err),
+    }
+}
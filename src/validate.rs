@@ -0,0 +1,173 @@
+use std::fmt;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::proc::{course::Course, tile_definitions::TileDefinitions, tile_graph::TileGraph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The course can be played but probably isn't what the designer wanted.
+    Warning,
+    /// The course can't be played.
+    Error,
+}
+
+/// Something wrong with a course.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// Checks a course for tiles that overlap, are unknown, are cut off or are floating,
+/// and that it has a tee and a cup that can be reached from it.
+pub fn validate_course(course: &Course, defs: &TileDefinitions) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let tiles = &course.tiles;
+
+    // Tiles on top of each other.
+    let mut positions: HashMap<IVec3, usize> = HashMap::default();
+    for tile in tiles.iter() {
+        *positions.entry(tile.position).or_default() += 1;
+    }
+    let mut overlaps: Vec<_> = positions.iter().filter(|(_, count)| **count > 1).collect();
+    overlaps.sort_by_key(|(position, _)| position.to_array());
+    for (position, count) in overlaps {
+        problems.push(Problem::error(format!(
+            "{} tiles overlap at {}",
+            count, position
+        )));
+    }
+
+    // Tiles without definitions.
+    for tile in tiles.iter() {
+        if let Err(err) = defs.get(&tile.tile_type) {
+            problems.push(Problem::error(format!("{} at {}", err, tile.position)));
+        }
+    }
+
+    // The tee and the cup.
+    let tee = tiles
+        .iter()
+        .position(|tile| tile.position == course.tee.position);
+    if tee.is_none() {
+        problems.push(Problem::error(format!(
+            "The tee at {} isn't on a tile",
+            course.tee.position
+        )));
+    }
+    let cups: Vec<usize> = (0..tiles.len())
+        .filter(|i| defs.get(&tiles[*i].tile_type).map_or(false, |def| def.cup))
+        .collect();
+    match cups.len() {
+        0 => problems.push(Problem::error("The course doesn't have a cup".to_string())),
+        1 => {}
+        count => problems.push(Problem::warning(format!(
+            "The course has {} cups, only the one at {} is used",
+            count, tiles[cups[0]].position
+        ))),
+    }
+
+    // Groups of tiles that don't touch the rest of the course.
+    let islands = find_islands(tiles.iter().map(|tile| tile.position).collect());
+    if islands.len() > 1 {
+        problems.push(Problem::warning(format!(
+            "The course is split into {} islands",
+            islands.len()
+        )));
+        for island in islands
+            .iter()
+            .filter(|island| !tee.map_or(false, |tee| island.contains(&tee)))
+        {
+            problems.push(Problem::warning(format!(
+                "{} tiles starting at {} are cut off from the tee",
+                island.len(),
+                tiles[island[0]].position
+            )));
+        }
+    }
+
+    // Whether the ball can roll from the tee to the cup without leaving the course.
+    let graph = TileGraph::new(tiles, defs);
+    if let (Some(tee), Some(cup)) = (tee, cups.first()) {
+        if !graph.reachable(tee)[*cup] {
+            problems.push(Problem::error(format!(
+                "The cup at {} can't be reached from the tee without leaving the course",
+                tiles[*cup].position
+            )));
+        }
+    }
+
+    // Tiles that don't join onto another tile and have nothing under them.
+    if tiles.len() > 1 {
+        for (index, tile) in tiles.iter().enumerate() {
+            let below = positions.contains_key(&(tile.position - IVec3::Y));
+            if graph.neighbours[index].is_empty() && !below {
+                problems.push(Problem::warning(format!(
+                    "The tile at {} is floating with nothing supporting it",
+                    tile.position
+                )));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Groups tiles that are next to each other, including tiles a step up or down.
+/// Returns the indices of the tiles in each group.
+fn find_islands(positions: Vec<IVec3>) -> Vec<Vec<usize>> {
+    let touching = |a: IVec3, b: IVec3| {
+        let offset = (a - b).abs();
+        offset.x + offset.z == 1 && offset.y <= 1
+    };
+
+    let mut island_of = vec![None; positions.len()];
+    let mut islands = Vec::new();
+    for start in 0..positions.len() {
+        if island_of[start].is_some() {
+            continue;
+        }
+
+        let mut island = vec![start];
+        island_of[start] = Some(islands.len());
+        let mut open = vec![start];
+        while let Some(current) = open.pop() {
+            for next in 0..positions.len() {
+                if island_of[next].is_none() && touching(positions[current], positions[next]) {
+                    island_of[next] = Some(islands.len());
+                    island.push(next);
+                    open.push(next);
+                }
+            }
+        }
+        islands.push(island);
+    }
+
+    islands
+}
@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_golf::proc::{
+    course::{Course, Tee},
+    course_code::{decode_course, encode_course, tile_order, COURSE_CODE_VERSION},
+    generator::{generate_course, GeneratorSettings},
+    load_tile_packs_from,
+    migrate::COURSE_VERSION,
+    tile::{Flip, Orientation, Tile},
+};
+
+fn assets() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"))
+}
+
+/// Checks a course comes back the same after being encoded, apart from the order of it's tiles.
+fn assert_round_trip(course: &Course) {
+    let code = encode_course(course);
+    let decoded = decode_course(&code).expect("the code should decode");
+
+    let mut expected = course.clone();
+    expected.tiles.sort_by_key(tile_order);
+    assert_eq!(
+        ron::ser::to_string(&expected).unwrap(),
+        ron::ser::to_string(&decoded).unwrap(),
+        "{} didn't survive being encoded as {}",
+        course.name,
+        code
+    );
+}
+
+/// Adds a number the way course codes store them.
+fn varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Makes a code by hand, for a course with one run of flat tiles and a par that's stored as is.
+fn hand_made_code(par: u64, start: IVec3, length: u64) -> String {
+    let mut bytes = vec![COURSE_CODE_VERSION];
+    let string = |bytes: &mut Vec<u8>, value: &str| {
+        varint(bytes, value.len() as u64);
+        bytes.extend(value.as_bytes());
+    };
+    let position = |bytes: &mut Vec<u8>, position: IVec3| {
+        for value in position.to_array() {
+            varint(bytes, ((value << 1) ^ (value >> 31)) as u32 as u64);
+        }
+    };
+
+    string(&mut bytes, "Hand made");
+    varint(&mut bytes, par);
+    position(&mut bytes, IVec3::ZERO);
+    bytes.push(0);
+    varint(&mut bytes, 1);
+    string(&mut bytes, "base:flat");
+    varint(&mut bytes, 1);
+    position(&mut bytes, start);
+    varint(&mut bytes, length);
+    for _ in 0..length {
+        varint(&mut bytes, 0);
+    }
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn tile(position: IVec3, rotation: Orientation, flip: Flip, tile_type: &str) -> Tile {
+    Tile {
+        position,
+        rotation,
+        flip,
+        tile_type: tile_type.to_string(),
+    }
+}
+
+#[test]
+fn round_trips_the_first_course() {
+    let course = Course::load_file(assets().join("courses/first.course.ron")).unwrap();
+    assert_round_trip(&course);
+}
+
+#[test]
+fn round_trips_par_flips_and_negative_positions() {
+    let course = Course {
+        version: COURSE_VERSION,
+        name: "Flipped ✓".to_string(),
+        par: Some(3),
+        tee: Tee {
+            position: IVec3::new(-5, -2, 300),
+            facing: Orientation::West,
+        },
+        tiles: vec![
+            tile(
+                IVec3::new(-5, -2, 300),
+                Orientation::South,
+                Flip::None,
+                "base:flat",
+            ),
+            tile(
+                IVec3::new(-4, -2, 300),
+                Orientation::East,
+                Flip::Mirror,
+                "base:ramp",
+            ),
+            tile(
+                IVec3::new(-3, -2, 300),
+                Orientation::West,
+                Flip::None,
+                "base:ramp",
+            ),
+            tile(
+                IVec3::new(-1, -2, 300),
+                Orientation::North,
+                Flip::Mirror,
+                "pack:odd",
+            ),
+            tile(
+                IVec3::new(-1, -1, 300),
+                Orientation::North,
+                Flip::None,
+                "base:hole",
+            ),
+            tile(
+                IVec3::new(-70000, 1000000, 0),
+                Orientation::East,
+                Flip::None,
+                "base:flat",
+            ),
+        ],
+    };
+    assert_round_trip(&course);
+}
+
+#[test]
+fn round_trips_generated_courses() {
+    let (defs, _) = load_tile_packs_from(assets()).unwrap();
+    let settings = GeneratorSettings {
+        length: 20,
+        turns: 4,
+        elevation_changes: 3,
+        hazard_density: 0.3,
+    };
+    for seed in 0..10 {
+        assert_round_trip(&generate_course(seed, &settings, &defs).unwrap());
+    }
+}
+
+#[test]
+fn codes_are_url_safe() {
+    let course = Course::load_file(assets().join("courses/first.course.ron")).unwrap();
+    let code = encode_course(&course);
+    assert!(code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+}
+
+#[test]
+fn rejects_broken_codes() {
+    let course = Course::load_file(assets().join("courses/first.course.ron")).unwrap();
+    let code = encode_course(&course);
+
+    assert!(decode_course(&code[..code.len() / 2]).is_err());
+    assert!(decode_course("not a course code!").is_err());
+
+    // A version from the future.
+    let mut bytes = base64::decode_config(&code, base64::URL_SAFE_NO_PAD).unwrap();
+    bytes[0] = 255;
+    assert!(decode_course(&base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)).is_err());
+}
+
+#[test]
+fn rejects_codes_that_overflow() {
+    let course = decode_course(&hand_made_code(0, IVec3::ZERO, 2)).unwrap();
+    assert_eq!(course.par, None);
+    assert_eq!(course.tiles.len(), 2);
+
+    // The par is stored one higher so 0 can mean there isn't one.
+    let course = decode_course(&hand_made_code(1 << 32, IVec3::ZERO, 1)).unwrap();
+    assert_eq!(course.par, Some(u32::MAX));
+    assert!(decode_course(&hand_made_code((1 << 32) + 1, IVec3::ZERO, 1)).is_err());
+
+    let edge = IVec3::new(i32::MAX, 0, 0);
+    let course = decode_course(&hand_made_code(0, edge, 1)).unwrap();
+    assert_eq!(course.tiles[0].position, edge);
+    assert!(decode_course(&hand_made_code(0, edge, 2)).is_err());
+}
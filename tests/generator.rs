@@ -0,0 +1,21 @@
+use std::path::Path;
+
+use bevy_golf::proc::{
+    generator::{generate_course, GeneratorSettings},
+    load_tile_packs_from,
+};
+
+fn assets() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"))
+}
+
+/// What seed 1 generates with the default settings.
+/// If this changes, every shared seed and daily course changes too.
+const SEED_1: &str = r#"(version:1,name:"Generated #1",tee:(position:(0,0,0),facing:North),tiles:[(position:(2,1,-9),rotation:North,tile_type:"base:hole"),(position:(1,1,-8),rotation:North,tile_type:"base:hazard"),(position:(2,1,-8),rotation:North,tile_type:"base:flat"),(position:(2,1,-7),rotation:North,tile_type:"base:flat"),(position:(2,1,-6),rotation:West,tile_type:"base:ramp"),(position:(2,0,-5),rotation:North,tile_type:"base:flat"),(position:(2,0,-4),rotation:North,tile_type:"base:flat"),(position:(2,0,-3),rotation:North,tile_type:"base:flat"),(position:(2,0,-2),rotation:North,tile_type:"base:flat"),(position:(0,0,-1),rotation:North,tile_type:"base:corner"),(position:(1,0,-1),rotation:North,tile_type:"base:flat"),(position:(2,0,-1),rotation:South,tile_type:"base:corner"),(position:(0,0,0),rotation:North,tile_type:"base:flat")])"#;
+
+#[test]
+fn seeds_always_generate_the_same_course() {
+    let (defs, _) = load_tile_packs_from(assets()).unwrap();
+    let course = generate_course(1, &GeneratorSettings::default(), &defs).unwrap();
+    assert_eq!(ron::ser::to_string(&course).unwrap(), SEED_1);
+}
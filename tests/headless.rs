@@ -0,0 +1,24 @@
+use bevy_golf::{ball::BALL_RADIUS, headless::Simulation};
+
+#[test]
+fn loads_the_first_course() {
+    let mut simulation = Simulation::load("courses/first.course.ron").unwrap();
+    assert!(!simulation.tiles().is_empty());
+    assert!(simulation.cup().is_some());
+}
+
+#[test]
+fn takes_a_shot_on_the_first_course() {
+    let mut simulation = Simulation::load("courses/first.course.ron").unwrap();
+    let ball = simulation.place_on_tee(0, 1);
+    let start = simulation.ball_position(ball).unwrap();
+
+    let result = simulation.shoot(ball, simulation.tee().forward() * 0.3);
+    assert!(!result.out_of_bounds, "the ball left the course");
+    assert!(result.steps > 0);
+    assert!(
+        result.position.distance(start) > BALL_RADIUS,
+        "the ball didn't move from {}",
+        start
+    );
+}
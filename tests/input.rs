@@ -0,0 +1,181 @@
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use bevy_golf::input::{Action, ActionPlugin, ActionState, Binding, Bindings, Device};
+
+/// Makes an app that only turns inputs into actions, with the default bindings.
+fn app() -> App {
+    let mut app = App::new();
+    app.init_resource::<Input<KeyCode>>()
+        .init_resource::<Input<MouseButton>>()
+        .init_resource::<Gamepads>()
+        .init_resource::<Input<GamepadButton>>()
+        .init_resource::<Axis<GamepadAxis>>()
+        .add_event::<MouseWheel>()
+        .add_plugin(ActionPlugin);
+
+    // Run the startup systems, then make sure a bindings file doesn't change what's being tested.
+    app.update();
+    *app.world.resource_mut::<Bindings>() = Bindings::default();
+    app
+}
+
+/// Plays a frame where the keys and mouse buttons change, the same way the input systems would.
+fn frame(app: &mut App, keys: &[(KeyCode, bool)], buttons: &[(MouseButton, bool)]) {
+    let mut key_input = app.world.resource_mut::<Input<KeyCode>>();
+    key_input.clear();
+    for (key, pressed) in keys {
+        match pressed {
+            true => key_input.press(*key),
+            false => key_input.release(*key),
+        }
+    }
+
+    let mut button_input = app.world.resource_mut::<Input<MouseButton>>();
+    button_input.clear();
+    for (button, pressed) in buttons {
+        match pressed {
+            true => button_input.press(*button),
+            false => button_input.release(*button),
+        }
+    }
+
+    app.update();
+}
+
+fn actions(app: &App) -> &ActionState {
+    app.world.resource::<ActionState>()
+}
+
+#[test]
+fn presses_and_releases_an_action() {
+    let mut app = app();
+
+    frame(&mut app, &[(KeyCode::R, true)], &[]);
+    assert!(actions(&app).just_pressed(Action::Reset));
+    assert!(actions(&app).pressed(Action::Reset));
+    assert_eq!(actions(&app).device(Action::Reset), Some(Device::Keyboard));
+
+    frame(&mut app, &[], &[]);
+    assert!(!actions(&app).just_pressed(Action::Reset));
+    assert!(actions(&app).pressed(Action::Reset));
+
+    frame(&mut app, &[(KeyCode::R, false)], &[]);
+    assert!(actions(&app).just_released(Action::Reset));
+    assert!(!actions(&app).pressed(Action::Reset));
+}
+
+#[test]
+fn only_releases_once_every_input_is_let_go() {
+    let mut app = app();
+
+    frame(
+        &mut app,
+        &[(KeyCode::Space, true)],
+        &[(MouseButton::Left, true)],
+    );
+    assert!(actions(&app).pressed(Action::Charge));
+
+    // The mouse is still charging.
+    frame(&mut app, &[(KeyCode::Space, false)], &[]);
+    assert!(actions(&app).pressed(Action::Charge));
+    assert!(!actions(&app).just_released(Action::Charge));
+    assert_eq!(actions(&app).device(Action::Charge), Some(Device::Mouse));
+
+    frame(&mut app, &[], &[(MouseButton::Left, false)]);
+    assert!(actions(&app).just_released(Action::Charge));
+    assert!(!actions(&app).pressed(Action::Charge));
+    assert_eq!(actions(&app).device(Action::Charge), Some(Device::Mouse));
+
+    frame(&mut app, &[], &[]);
+    assert!(!actions(&app).just_released(Action::Charge));
+}
+
+#[test]
+fn rebinding_replaces_the_binding_on_the_same_device() {
+    let mut bindings = Bindings::default();
+    bindings.rebind(Action::Charge, Binding::Key(KeyCode::C));
+
+    let charge = bindings.get(Action::Charge);
+    assert!(charge.contains(&Binding::Key(KeyCode::C)));
+    assert!(!charge.contains(&Binding::Key(KeyCode::Space)));
+    // The mouse and gamepad bindings are left alone.
+    assert!(charge.contains(&Binding::Mouse(MouseButton::Left)));
+    assert!(charge
+        .iter()
+        .any(|binding| binding.device() == Device::Gamepad));
+    assert_eq!(
+        charge
+            .iter()
+            .filter(|binding| binding.device() == Device::Keyboard)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn rebinding_an_unbound_action_adds_it() {
+    let mut bindings = Bindings(Default::default());
+    assert!(bindings.get(Action::Shoot).is_empty());
+
+    bindings.rebind(Action::Shoot, Binding::Mouse(MouseButton::Middle));
+    assert_eq!(
+        bindings.get(Action::Shoot),
+        &[Binding::Mouse(MouseButton::Middle)]
+    );
+}
+
+#[test]
+fn default_bindings_keep_the_fly_controls_to_themselves() {
+    let bindings = Bindings::default();
+    let fly = [
+        Action::FlyForward,
+        Action::FlyBack,
+        Action::FlyLeft,
+        Action::FlyRight,
+        Action::FlyUp,
+        Action::FlyDown,
+    ];
+    // The camera only rotates outside of the free camera, so it can share the fly keys.
+    let others = Action::ALL.into_iter().filter(|action| {
+        !fly.contains(action)
+            && !matches!(action, Action::RotateCameraLeft | Action::RotateCameraRight)
+    });
+
+    for other in others {
+        for action in fly {
+            for binding in bindings.get(action) {
+                assert!(
+                    !bindings.get(other).contains(binding),
+                    "{:?} shares {:?} with {:?}",
+                    action,
+                    binding,
+                    other
+                );
+                assert!(
+                    !matches!(binding, Binding::Key(KeyCode::LControl | KeyCode::RControl)),
+                    "{:?} is bound to Control, which shortcuts use",
+                    action
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn shortcuts_dont_take_the_actions_on_their_keys() {
+    let mut app = app();
+
+    frame(
+        &mut app,
+        &[(KeyCode::LControl, true), (KeyCode::S, true)],
+        &[],
+    );
+    assert!(actions(&app).just_pressed(Action::SaveCourse));
+    assert!(!actions(&app).pressed(Action::FlyBack));
+
+    // Without Control it's just the key.
+    frame(&mut app, &[(KeyCode::LControl, false)], &[]);
+    frame(&mut app, &[(KeyCode::S, false)], &[]);
+    frame(&mut app, &[(KeyCode::S, true)], &[]);
+    assert!(actions(&app).pressed(Action::FlyBack));
+    assert!(!actions(&app).just_pressed(Action::SaveCourse));
+}
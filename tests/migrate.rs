@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_golf::proc::migrate::{read_course, read_tile_pack, COURSE_VERSION, TILE_PACK_VERSION};
+
+#[test]
+fn migrates_a_course_with_numbered_tile_types() {
+    let migrated = read_course(include_bytes!("fixtures/v0.course.ron")).unwrap();
+    assert_eq!(migrated.from_version, 0);
+    assert!(migrated.outdated(COURSE_VERSION));
+
+    let course = migrated.value;
+    assert_eq!(course.version, COURSE_VERSION);
+    assert_eq!(course.name, "Numbered");
+    let types: Vec<&str> = course
+        .tiles
+        .iter()
+        .map(|tile| tile.tile_type.as_str())
+        .collect();
+    assert_eq!(types, ["base:flat", "base:ramp", "base:hole"]);
+    assert_eq!(course.tiles[2].position, IVec3::new(0, -1, -2));
+}
+
+#[test]
+fn migrates_a_tile_pack_that_was_a_list() {
+    let migrated = read_tile_pack(include_bytes!("fixtures/v0.tiles.ron")).unwrap();
+    assert_eq!(migrated.from_version, 0);
+    assert!(migrated.outdated(TILE_PACK_VERSION));
+
+    let keys: Vec<&str> = migrated.value.iter().map(|def| def.key.as_str()).collect();
+    assert_eq!(keys, ["base:flat", "base:hole", "extra:bump"]);
+    assert!(migrated.value[1].cup);
+}
+
+#[test]
+fn reads_the_latest_version_as_is() {
+    let migrated = read_course(include_bytes!("../assets/courses/first.course.ron")).unwrap();
+    assert_eq!(migrated.from_version, COURSE_VERSION);
+    assert!(!migrated.outdated(COURSE_VERSION));
+}
+
+#[test]
+fn rejects_files_it_cant_read_instead_of_migrating_them() {
+    // A version that isn't a number isn't a missing version.
+    assert!(read_course(
+        br#"(version: "1", name: "Broken", tee: (position: (0, 0, 0), facing: North), tiles: [])"#
+    )
+    .is_err());
+    // Neither is a file that's cut short.
+    assert!(read_course(br#"(version: 1, name: "Broken", tee: ("#).is_err());
+    assert!(read_tile_pack(b"(version: 1, tiles: [").is_err());
+    // Nor a version from the future.
+    assert!(read_course(
+        br#"(version: 99, name: "Future", tee: (position: (0, 0, 0), facing: North), tiles: [])"#
+    )
+    .is_err());
+}
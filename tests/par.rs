@@ -0,0 +1,11 @@
+use bevy_golf::par::estimate_par;
+
+#[test]
+fn estimates_par_for_the_first_course() {
+    let estimate = estimate_par("courses/first.course.ron").unwrap();
+    assert!(estimate.route.tiles.len() >= 2);
+    assert!(estimate.route.length > 0.0);
+    assert!(estimate.route_par >= 2);
+    assert!(estimate.simulated_shots >= 1.0);
+    assert!(estimate.par >= 2);
+}
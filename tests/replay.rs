@@ -0,0 +1,71 @@
+use bevy_golf::{
+    headless::Simulation,
+    replay::{Playback, Recorder, Replay},
+};
+
+const COURSE: &str = "courses/first.course.ron";
+/// How long a replay gets to play back before it's given up on.
+const MAX_PLAYBACK_STEPS: usize = 60 * 60 * 4;
+
+/// Takes a few shots from the tee and records them.
+fn record() -> Replay {
+    let mut simulation = Simulation::load(COURSE).unwrap();
+    let tee = simulation.tee();
+    let ball = simulation.place_on_tee(0, 1);
+    for power in [0.3, 0.6, 0.2] {
+        simulation.shoot(ball, tee.forward() * power);
+    }
+
+    let shots = simulation.world().resource::<Recorder>().shots.clone();
+    assert_eq!(shots.len(), 3, "every shot should be recorded");
+    Replay {
+        course: COURSE.to_string(),
+        players: 1,
+        shots,
+    }
+}
+
+/// Plays a replay back the same way it was recorded, returning the playback once every shot has been checked.
+fn play_back(replay: Replay) -> Playback {
+    let mut simulation = Simulation::load(&replay.course).unwrap();
+    simulation.world().insert_resource(Playback::new(replay));
+    simulation.place_on_tee(0, 1);
+
+    for _ in 0..MAX_PLAYBACK_STEPS {
+        if simulation.world().resource::<Playback>().finished() {
+            break;
+        }
+        simulation.step();
+    }
+    simulation.world().remove_resource::<Playback>().unwrap()
+}
+
+#[test]
+fn replays_match_their_recording() {
+    let playback = play_back(record());
+    assert!(playback.finished(), "the replay didn't finish");
+    playback.result().unwrap();
+}
+
+#[test]
+fn replays_fail_when_a_shot_diverges() {
+    let mut replay = record();
+    let (position, _) = replay.shots[1].path.last_mut().unwrap();
+    position.x += 1.0;
+
+    let playback = play_back(replay);
+    assert!(playback.finished(), "the replay didn't finish");
+    assert!(playback.result().is_err());
+}
+
+#[test]
+fn rejects_replays_without_players() {
+    let path = std::env::temp_dir().join("bevy-golf-no-players.replay.ron");
+    let replay = Replay {
+        course: COURSE.to_string(),
+        players: 0,
+        shots: Vec::new(),
+    };
+    replay.save(&path).unwrap();
+    assert!(Replay::load(&path).is_err());
+}